@@ -6,7 +6,7 @@ use logos::{Logos, Span};
 
 use crate::token::{Token, TokenStream};
 use crate::interpret::{
-	EvalError, EvalResult,
+	BigInt, Context, EvalError, EvalResult, Str,
 	StackFrame, Value,
 };
 
@@ -19,17 +19,21 @@ mod lvalue;
 mod module;
 mod procedure;
 mod statement;
+mod type_decl;
 mod types;
 
 pub use self::expression::{BinOp, Expr, ExprErr};
-pub use self::block_expr::{BlockExpr, BlockExprErr};
-pub use self::function::Function;
+pub use self::block_expr::{Binding, BlockExpr, BlockExprErr};
+pub use self::function::{Closure, Function};
+#[cfg(test)]
+pub use self::function::CALLS;
 pub use self::item::Item;
 pub use self::literal::Literal;
 pub use self::lvalue::{Deref, LValue, LValErr};
 pub use self::module::Module;
 pub use self::procedure::{Param, Procedure, ProcDef};
 pub use self::statement::Stmt;
+pub use self::type_decl::{DeclKind, TypeDecl};
 pub use self::types::{Type, TypeErr};
 
 pub type ParseResult<T> = Result<T, ParseError>;
@@ -72,13 +76,15 @@ pub struct Parser<'src> {
 	line: usize,
 	/// byte position of last newline character in source
 	last_nl: usize,
+	/// type parameters of the generic item being parsed
+	type_params: Vec<String>,
 }
 
 impl<'src> Parser<'src> {
 	pub fn new(src: &'src str) -> Self {
 		let mut lexer = Token::lexer(src);
 		let curr = lexer.next();
-		Parser { lexer, curr, line: 1, last_nl: 0 }
+		Parser { lexer, curr, line: 1, last_nl: 0, type_params: Vec::new() }
 	}
 	
 	pub fn slice(&self) -> &str {
@@ -130,16 +136,69 @@ impl<'src> Parser<'src> {
 		while self.expect(Token::Newline).is_some() {}
 	}
 	
+	/// Parses the name of a type, which may be inside of modules, like
+	/// `io::Byte`. The path is kept as one string.
+	pub fn parse_type_path(&mut self) -> ParseResult<String> {
+		let mut path = String::new();
+		
+		// a lowercase name is only a module if `::` comes after it
+		while self.peek() == Some(&Token::VarIdent) {
+			let mut lookahead = self.clone();
+			lookahead.next();
+			if lookahead.peek() != Some(&Token::Scope) {
+				Err("type name (types are capitalized)")?;
+			}
+			
+			path.push_str(self.slice());
+			self.next();
+			self.next();
+			path.push_str("::");
+		}
+		
+		match self.peek() {
+			Some(Token::ConIdent) => path.push_str(self.slice()),
+			_ => Err("a type name")?,
+		}
+		self.next();
+		
+		Ok(path)
+	}
+	
+	/// Parses an optional list of type parameters, like `[T, U]`.
+	pub fn parse_type_params(&mut self) -> ParseResult<Vec<String>> {
+		let mut params = Vec::new();
+		
+		if self.expect(Token::LBracket).is_none() {
+			return Ok(params);
+		}
+		loop {
+			match self.peek() {
+				Some(Token::RBracket) => break,
+				Some(Token::ConIdent) => {
+					params.push(self.slice().to_string());
+					self.next();
+					
+					match self.peek() {
+						Some(Token::Comma) => { self.next(); }
+						Some(Token::RBracket) => {}
+						_ => Err("`,` or `]` in type parameter list")?,
+					}
+				}
+				_ => Err("a type parameter name")?,
+			}
+		}
+		self.next();
+		
+		Ok(params)
+	}
+	
 	pub fn parse_file_module(&mut self) -> ParseResult<Vec<Item>> {
 		let mut items = Vec::new();
 		
 		while self.peek().is_some() {
-			match self.peek().unwrap() {
-				Token::Newline => {
-					self.next();
-					continue;
-				}
-				_ => {}
+			if self.peek() == Some(&Token::Newline) {
+				self.next();
+				continue;
 			}
 			items.push(self.parse_item()?);
 		}
@@ -9,7 +9,15 @@ pub enum BlockExpr {
 	
 	If(Expr, Box<Self>, Box<Self>),
 	
-	Let(String, Type, Expr, Box<Self>),
+	Let(Binding, Type, Expr, Box<Self>),
+}
+
+/// The names a `let` binds. A tuple of names takes apart a tuple value, like
+/// `let (q, r) = div_mod(n, d)`.
+#[derive(Debug, Clone)]
+pub enum Binding {
+	Name(String),
+	Tuple(Vec<Self>),
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +44,7 @@ impl Parser<'_> {
 				
 				// parse main block
 				let main_expr = Box::new(self.parse_block_expr()?);
+				self.skip_newlines();
 				
 				self.expect(Token::Else)
 					.ok_or("`else` in `if` expression")?;
@@ -56,30 +65,35 @@ impl Parser<'_> {
 				BlockExpr::If(test, main_expr, else_block)
 			}
 			
+			// let x = 1, y: U32 = x + 1
 			Some(Token::Let) => {
 				self.next();
 				
-				let name = match self.peek() {
-					Some(Token::VarIdent) => self.slice().to_string(),
-					_ => Err("variable name for let binding")?,
-					//_ => Err(BlockExprErr::NotVarName)?,
-				};
-				
-				// get optional `: <type>`
-				let typ = match self.peek() {
-					Some(Token::Colon) => {
-						self.next();
-						self.parse_type()?
+				// each binding can see the ones before it
+				let mut bindings = Vec::new();
+				loop {
+					let binding = self.parse_binding()?;
+					
+					// get optional `: <type>`
+					let typ = match self.peek() {
+						Some(Token::Colon) => {
+							self.next();
+							self.parse_type()?
+						}
+						_ => Type::Infer,
+					};
+					
+					// expect '='
+					self.expect(Token::Eq)
+						.ok_or("`=` at let binding")?;
+						//.ok_or(BlockExprErr::NoEq)?;
+					
+					bindings.push((binding, typ, self.parse_expr()?));
+					
+					if self.expect(Token::Comma).is_none() {
+						break;
 					}
-					_ => Type::Infer,
-				};
-				
-				// expect '='
-				self.expect(Token::Eq)
-					.ok_or("`=` at let binding")?;
-					//.ok_or(BlockExprErr::NoEq)?;
-				
-				let val = self.parse_expr()?;
+				}
 				
 				self.expect(Token::Newline)
 					.ok_or("newline at let binding")?;
@@ -87,9 +101,13 @@ impl Parser<'_> {
 				
 				self.skip_newlines();
 				
-				let scope = Box::new(self.parse_block_expr()?);
+				let scope = self.parse_block_expr()?;
 				
-				BlockExpr::Let(name, typ, val, scope)
+				// nest the bindings from the last one out
+				return Ok(bindings.into_iter().rev()
+					.fold(scope, |scope, (binding, typ, val)|
+						BlockExpr::Let(binding, typ, val, Box::new(scope))
+					));
 			}
 			
 			_ => BlockExpr::Inline(self.parse_expr()?)
@@ -103,27 +121,124 @@ impl Parser<'_> {
 		
 		Ok(block_expr)
 	}
+	
+	// binding ::= name | "(" binding { "," binding } ")"
+	fn parse_binding(&mut self) -> ParseResult<Binding> {
+		match self.peek() {
+			Some(Token::VarIdent) => {
+				let name = self.slice().to_string();
+				self.next();
+				Ok(Binding::Name(name))
+			}
+			Some(Token::LParen) => {
+				self.next();
+				
+				let mut bindings = Vec::new();
+				loop {
+					bindings.push(self.parse_binding()?);
+					
+					match self.peek() {
+						Some(Token::Comma) => { self.next(); }
+						Some(Token::RParen) => break,
+						_ => Err("`,` or `)` in let binding")?,
+					}
+				}
+				self.next();
+				
+				Ok(Binding::Tuple(bindings))
+			}
+			_ => Err("variable name for let binding")?,
+			//_ => Err(BlockExprErr::NotVarName)?,
+		}
+	}
+}
+
+impl Binding {
+	/// The names this binds, in the order they're bound.
+	pub fn names(&self) -> Vec<&str> {
+		match self {
+			Binding::Name(name) => vec![name],
+			Binding::Tuple(bindings) => bindings.iter().flat_map(Binding::names).collect(),
+		}
+	}
+	
+	/// Takes apart a value into the variables this binds, in the same order
+	/// as `names`.
+	fn bind(&self, value: Value, vars: &mut Vec<(String, Value)>) -> EvalResult<()> {
+		match (self, value) {
+			(Binding::Name(name), value) => vars.push((name.clone(), value)),
+			(Binding::Tuple(bindings), Value::Tuple(elems)) if bindings.len() == elems.len() =>
+				for (binding, elem) in bindings.iter().zip(elems) {
+					binding.bind(elem, vars)?;
+				}
+			(Binding::Tuple(bindings), value) => Err(EvalError::TypeMismatch {
+				expected: Type::Tuple(vec![Type::Infer; bindings.len()]),
+				got: value.get_type(),
+			})?,
+		}
+		Ok(())
+	}
+}
+
+impl BlockExpr {
+	/// Checks whether the variable with the given name is used anywhere in
+	/// this block, other than where a `let` shadows it.
+	pub fn uses_var(&self, name: &str) -> bool {
+		match self {
+			BlockExpr::Inline(expr) => expr.uses_var(name),
+			BlockExpr::If(test, then, otherwise) =>
+				test.uses_var(name) || then.uses_var(name) || otherwise.uses_var(name),
+			BlockExpr::Let(binding, _, val, scope) =>
+				val.uses_var(name)
+				|| (!binding.names().contains(&name) && scope.uses_var(name)),
+		}
+	}
 }
 
 use crate::interpret::StackFrame;
 impl BlockExpr {
 	pub fn eval(&self, ctx: &mut StackFrame) -> EvalResult<Value> {
+		self.eval_as(ctx, &Type::Infer)
+	}
+	
+	/// Evaluates the block, where the result is hinted to be the given type,
+	/// like a function's return type.
+	pub fn eval_as(&self, ctx: &mut StackFrame, hint: &Type) -> EvalResult<Value> {
 		match self {
-			BlockExpr::Inline(expr) => expr.eval(ctx),
+			BlockExpr::Inline(expr) => expr.eval_as(ctx, hint),
 			
 			BlockExpr::If(test, expr, else_expr) => {
 				if test.eval(ctx)? == Value::Bool(true) {
-					expr.eval(ctx)
+					expr.eval_as(ctx, hint)
 				} else {
-					else_expr.eval(ctx)
+					else_expr.eval_as(ctx, hint)
 				}
 			}
 			
-			BlockExpr::Let(name, _, val, scope) => {
-				let val = val.eval(ctx)?;
-				ctx.push(name.clone(), val);
-				let res = scope.eval(ctx);
-				ctx.pop();
+			// bindings only last until the end of their scope. functions run
+			// in a frame of their own, so these can't touch the caller's
+			// variables.
+			BlockExpr::Let(binding, typ, val, scope) => {
+				let resolved = ctx.resolve_type(typ)?;
+				let val = val.eval_as(ctx, &resolved)?;
+				if resolved != Type::Infer && val.get_type() != resolved {
+					return Err(EvalError::TypeMismatch {
+						expected: typ.clone(),
+						got: val.get_type(),
+					});
+				}
+				
+				let mut vars = Vec::new();
+				binding.bind(val, &mut vars)?;
+				let count = vars.len();
+				for (name, value) in vars {
+					ctx.push(name, value);
+				}
+				
+				let res = scope.eval_as(ctx, hint);
+				for _ in 0..count {
+					ctx.pop();
+				}
 				res
 			}
 		}
@@ -1,18 +1,31 @@
 /*!
-Expressions in Rever have 5 levels of precendence. From strongest to weakest:
-1. Parentheses
+Expressions in Rever have 7 levels of precendence. From strongest to weakest:
+1. Parentheses and casts: as
 2. Function calls
 3. Unary operators: not - (maybe: ! ~)
 4. Exponential operators: ^ << >> shl shr rol ror
-5. Multiplicative operators: * / mod as and
+5. Multiplicative operators: * / % and
 6. Additive operators: + - or xor
-7. Relational operators: = != ≠ < > <= ≤ >= ≥ in
+7. Relational operators: = != ≠ < > <= ≤ >= ≥
 
-Ideas:
-+ Chained relations, a la Python?
-+ In `if` statements, conjunctions is `,` and disjunction is `;` (from Prolog).
-  + No short-circuiting; like Pascal.
-  + Short-circuiting can be achieved using `and` and `or`.
+Exponential operators are applied left to right, except that `^` groups to the
+right with the `^`s that follow it: `a << b ^ c` is `(a << b) ^ c`, and
+`a ^ b ^ c` is `a ^ (b ^ c)`.
+
+`and`, `or`, and `xor` work on both booleans and numbers, where they're bitwise
+operations on the latter. `rol` and `ror` rotate bits, but unlike the `:<` and
+`:>` statements, they produce a new value instead of modifying a variable.
+
+Relational operators can be chained, a la Python: `a < b <= c` is the same as
+`a < b and b <= c`, except that `b` is only evaluated once.
+
+Conditions (the tests and assertions of `if` and `from` statements) have two
+more levels of precedence below relational operators:
+8. Conjunction: ,
+9. Disjunction: ;
+
+These are borrowed from Prolog, but like in Pascal, they don't short-circuit.
+Short-circuiting can be achieved using `and` and `or`.
 
 TODO:
 + Add precedences 2, 
@@ -20,19 +33,25 @@ TODO:
 
 use std::fmt;
 use std::error;
+use std::ops::Range;
+use std::rc::Rc;
 
 use super::*;
 
+/// Binary operators. `and`, `or`, and `xor` are logical operators on booleans
+/// and bitwise operators on numbers.
 #[derive(Debug, Clone)]
 pub enum BinOp {
 	// precedence 4
-	Exp,
+	Exp, Shl, Shr, Rol, Ror,
 	// precedence 5
 	Mul, Div, Mod, And,
 	// precedence 6
-	Add, Sub, Or, //Xor,
+	Add, Sub, Or, Xor,
 	// precedence 7
 	Eq, Ne, Lt, Gt, Le, Ge,
+	// precedences 8 and 9, only in conditions. never short-circuits.
+	Conj, Disj,
 }
 
 #[derive(Debug, Clone)]
@@ -40,15 +59,19 @@ pub enum Expr {
 	// precedence 1
 	Lit(Literal),
 	LVal(LValue),
-	//Cast(Box<Self>, Type),
+	Cast(Box<Self>, Type),
 	
 	// precedence 3
 	Neg(Box<Self>),
 	Not(Box<Self>),
-	App(String, Vec<Self>),
+	/// a function's name, its arguments, and where it was called
+	App(String, Vec<Self>, Range<usize>),
 	
 	// binary op, precendeces 4-7
 	BinOp(Box<Self>, BinOp, Box<Self>),
+	
+	// chain of relational ops, precedence 7
+	Chain(Box<Self>, Vec<(BinOp, Self)>),
 }
 
 #[derive(Debug, Clone)]
@@ -88,10 +111,35 @@ impl error::Error for ExprErr {
 }
 
 impl Parser<'_> {
-	// rel  -> expr {(=|≠|<|>|≤|≥|in) expr}
-	// expr -> term {(+|-|or) term}
-	// term -> exp {(*|/|mod|and) exp}
-	// exp  -> atom {^ atom}
+	// disj -> conj {; conj}
+	// conj -> rel {, rel}
+	pub fn parse_cond(&mut self) -> ParseResult<Expr> {
+		let mut cond = self.parse_cond_conj()?;
+		
+		while self.expect(Token::Semicolon).is_some() {
+			let conj = self.parse_cond_conj()?;
+			cond = Expr::BinOp(Box::new(cond), BinOp::Disj, Box::new(conj));
+		}
+		
+		Ok(cond)
+	}
+	
+	fn parse_cond_conj(&mut self) -> ParseResult<Expr> {
+		let mut conj = self.parse_expr()?;
+		
+		while self.expect(Token::Comma).is_some() {
+			let rel = self.parse_expr()?;
+			conj = Expr::BinOp(Box::new(conj), BinOp::Conj, Box::new(rel));
+		}
+		
+		Ok(conj)
+	}
+	
+	// rel  -> expr {(=|≠|<|>|≤|≥) expr}
+	// expr -> term {(+|-|or|xor) term}
+	// term -> exp {(*|/|%|and) exp}
+	// exp  -> unary {(^|<<|>>|shl|shr|rol|ror) unary}
+	// unary -> {- | not} atom
 	// atom -> ( expr )
 	//      -> expr 'as' type
 	//      -> factor
@@ -117,14 +165,15 @@ impl Parser<'_> {
 			exprs.push((op, expr));
 		}
 		
-		if exprs.is_empty() {
-			return Ok(first);
-		}
-		
-		let expr = exprs.into_iter()
-			.fold(first, |acc, (op, base)| {
-				Expr::BinOp(Box::new(acc), op, Box::new(base))
-			});
+		// more than one relation is a chain, a la python
+		let expr = match exprs.len() {
+			0 => first,
+			1 => {
+				let (op, expr) = exprs.pop().unwrap();
+				Expr::BinOp(Box::new(first), op, Box::new(expr))
+			}
+			_ => Expr::Chain(Box::new(first), exprs),
+		};
 		
 		Ok(expr)
 	}
@@ -134,12 +183,13 @@ impl Parser<'_> {
 		let first = self.parse_expr_mul()?;
 		let mut terms: Vec<(BinOp, Expr)> = Vec::new();
 		
-		// { ('+' | '-' | 'or') <term> }
+		// { ('+' | '-' | 'or' | 'xor') <term> }
 		loop {
 			let op = match self.peek() {
 				Some(Token::Plus)  => BinOp::Add,
 				Some(Token::Minus) => BinOp::Sub,
 				Some(Token::Or)    => BinOp::Or,
+				Some(Token::Xor)   => BinOp::Xor,
 			    _ => break
 			};
 			self.next();
@@ -193,48 +243,123 @@ impl Parser<'_> {
 	}
 	
 	pub fn parse_expr_exp(&mut self) -> ParseResult<Expr> {
-		// <exp>
-		let first = self.parse_expr_atom()?;
-		let mut exps = Vec::new();
+		// <unary>
+		let mut expr = self.parse_expr_unary()?;
 		
-		// { ('^') <exp> }
+		// { ('^' | '<<' | '>>' | 'shl' | 'shr' | 'rol' | 'ror') <unary> }
 		loop {
 			let op = match self.peek() {
-				Some(Token::Caret) => {}
+				Some(Token::Caret) => BinOp::Exp,
+				Some(Token::LShift | Token::Shl) => BinOp::Shl,
+				Some(Token::RShift | Token::Shr) => BinOp::Shr,
+				Some(Token::Rol) => BinOp::Rol,
+				Some(Token::Ror) => BinOp::Ror,
 				_ => break
 			};
 			self.next();
 			
-			let exp = self.parse_expr_atom()?;
-			exps.push(exp);
+			let right = match op {
+				BinOp::Exp => self.parse_expr_pow()?,
+				_ => self.parse_expr_unary()?,
+			};
+			expr = Expr::BinOp(Box::new(expr), op, Box::new(right));
 		}
 		
-		if exps.is_empty() {
-			return Ok(first);
-		}
+		Ok(expr)
+	}
+	
+	// parses the right side of a `^`, which takes any `^`s after it too
+	fn parse_expr_pow(&mut self) -> ParseResult<Expr> {
+		let base = self.parse_expr_unary()?;
 		
-		let last = exps.pop().unwrap();
-		let res = exps.into_iter().rfold(last, |acc, base| {
-			Expr::BinOp(Box::new(base), BinOp::Exp, Box::new(acc))
-		});
+		if self.expect(Token::Caret).is_some() {
+			let exp = self.parse_expr_pow()?;
+			return Ok(Expr::BinOp(Box::new(base), BinOp::Exp, Box::new(exp)));
+		}
 		
-		Ok(Expr::BinOp(Box::new(first), BinOp::Exp, Box::new(res)))
+		Ok(base)
+	}
+	
+	pub fn parse_expr_unary(&mut self) -> ParseResult<Expr> {
+		// { ('-' | 'not') } <atom>
+		match self.peek() {
+			Some(Token::Minus) => {
+				self.next();
+				Ok(Expr::Neg(Box::new(self.parse_expr_unary()?)))
+			}
+			Some(Token::Not) => {
+				self.next();
+				Ok(Expr::Not(Box::new(self.parse_expr_unary()?)))
+			}
+			_ => self.parse_expr_atom(),
+		}
 	}
 	
 	pub fn parse_expr_atom(&mut self) -> ParseResult<Expr> {
 		// check if there's an open parenthesis
-		let expr = match self.peek() {
+		let mut expr = match self.peek() {
+			// subexpressions and tuples. like tuple types, a tuple of one
+			// element needs a trailing comma, like `(x,)`.
 			Some(Token::LParen) => {
 				self.next();
 				
-				let expr = self.parse_expr()?;
+				let mut elems = Vec::new();
+				let mut trailing_comma = false;
+				loop {
+					match self.peek() {
+						Some(Token::RParen) => break,
+						Some(_) => {
+							elems.push(self.parse_expr()?);
+							
+							trailing_comma = match self.peek() {
+								Some(Token::Comma) => { self.next(); true }
+								Some(Token::RParen) => false,
+								_ => Err("`)` after subexpression")?,
+							};
+						}
+						None => Err("`)` after subexpression")?,
+					}
+				}
+				self.next();
 				
-				// make sure there's a closing parenthesis
-				self.expect(Token::RParen)
-					.ok_or("`)` after subexpression")?;
-					//.ok_or(ExprErr::UnclosedSubexpr)?;
+				if elems.len() == 1 && !trailing_comma {
+					elems.pop().unwrap()
+				} else {
+					Expr::Lit(Literal::Tuple(elems))
+				}
+			}
+			
+			// function application, like `f(x, y)`
+			Some(Token::VarIdent) if {
+				let mut lookahead = self.clone();
+				lookahead.next();
+				lookahead.peek() == Some(&Token::LParen)
+			} => {
+				let name = self.slice().to_string();
+				let start = self.span().start;
+				self.next();
+				self.next();
 				
-				expr
+				let mut args = Vec::new();
+				loop {
+					match self.peek() {
+						Some(Token::RParen) => break,
+						Some(_) => {
+							args.push(self.parse_expr()?);
+							
+							match self.peek() {
+								Some(Token::Comma) => { self.next(); }
+								Some(Token::RParen) => {}
+								_ => Err("`,` or `)` after function argument")?,
+							}
+						}
+						None => Err("`,` or `)` after function argument")?,
+					}
+				}
+				let span = start..self.span().end;
+				self.next();
+				
+				Expr::App(name, args, span)
 			}
 			
 			Some(Token::VarIdent) => {
@@ -250,143 +375,530 @@ impl Parser<'_> {
 		};
 		
 		// check for `as` casting
-		/*
 		while self.peek() == Some(&Token::As) {
 			self.next();
 			
 			let ty = self.parse_type()?;
 			//.ok_or(ExprErr::BadCast)?
 			
+			// reject casts that could never work
+			match expr.static_type() {
+				Some(from) if !from.can_cast_to(&ty) =>
+					Err("a type that the value can be cast to")?,
+				_ => {}
+			}
+			
 			expr = Expr::Cast(Box::new(expr), ty);
 		}
-		*/
+		
 		Ok(expr)
 	}
 }
 
 
+impl Expr {
+	/// Gets the type of this expression if it can be known without evaluating
+	/// it or knowing the types of any variables.
+	pub fn static_type(&self) -> Option<Type> {
+		match self {
+			Expr::Lit(lit) => lit.get_type(),
+			Expr::Cast(_, typ) => Some(typ.clone()),
+			Expr::BinOp(_,
+				BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt
+				| BinOp::Le | BinOp::Ge
+				| BinOp::Conj | BinOp::Disj,
+			_) => Some(Type::Bool),
+			Expr::Chain(..) => Some(Type::Bool),
+			_ => None,
+		}
+	}
+	
+	/// Checks whether the variable with the given name is used anywhere in
+	/// this expression.
+	pub fn uses_var(&self, name: &str) -> bool {
+		match self {
+			Expr::Lit(Literal::Array(elems) | Literal::Tuple(elems)) =>
+				elems.iter().any(|e| e.uses_var(name)),
+			Expr::Lit(Literal::Struct(_, fields)) =>
+				fields.iter().any(|(_, e)| e.uses_var(name)),
+			Expr::Lit(Literal::Variant(_, Some(e))) => e.uses_var(name),
+			Expr::Lit(Literal::Fn(func)) =>
+				!func.params.iter().any(|(param, _)| param == name)
+				&& func.body.uses_var(name),
+			Expr::Lit(_) => false,
+			Expr::LVal(lval) => lval.uses_var(name),
+			Expr::Neg(e) | Expr::Not(e) | Expr::Cast(e, _) => e.uses_var(name),
+			Expr::App(_, args, _) => args.iter().any(|e| e.uses_var(name)),
+			Expr::BinOp(l, _, r) => l.uses_var(name) || r.uses_var(name),
+			Expr::Chain(first, rest) =>
+				first.uses_var(name) || rest.iter().any(|(_, e)| e.uses_var(name)),
+		}
+	}
+}
+
 // rel  -> expr {(=|≠|<|>|≤|≥|in) expr}
 // expr -> term {(+|-|or) term}
 // term -> exp {(*|/|mod|and) exp}
-// exp  -> atom {^ atom}
+// exp  -> unary {^ unary}
+// unary -> {- | not} atom
 // atom -> ( expr )
 //      -> expr 'as' type
 //      -> factor
 impl Expr {
 	pub fn eval(&self, ctx: &StackFrame) -> EvalResult<Value> {
+		self.eval_as(ctx, &Type::Infer)
+	}
+	
+	/// Evaluates the expression, where any number literals that can't get a
+	/// type from the values they're used with are given the hinted type.
+	pub fn eval_as(&self, ctx: &StackFrame, hint: &Type) -> EvalResult<Value> {
 		match self {
-			Expr::Lit(lit) => lit.eval(ctx),
+			Expr::Lit(lit) => lit.eval_as(ctx, hint),
+			// procedures are values too, unless a variable has the same name
+			Expr::LVal(lval) if lval.ops.is_empty() && ctx.get_var(&lval.id).is_none() =>
+				match ctx.items.find_proc(&lval.id) {
+					Some(proc) => Ok(Value::Proc(
+						proc.name.clone(),
+						ctx.items.expand(&proc.get_type())?,
+					)),
+					None => match ctx.items.find_fn(&lval.id) {
+						Some(func) => Ok(Value::Fn(Rc::new(Closure {
+							func: func.clone(),
+							captured: Vec::new(),
+						}))),
+						None => lval.eval(ctx),
+					}
+				}
 			Expr::LVal(lval) => lval.eval(ctx),
-			/*
-			Expr::Cast(e, typ) => match (typ, e.eval(t)?) {
-				(Type::Unit, _) => Ok(Value::Nil),
-				(Type::Int, Value::Uint(u))  => Ok(Value::U32(u as i64)),
-				(Type::UInt, Value::Bool(b)) => Ok(Value::Uint(b as u64)),
-				(Type::UInt, Value::U32(i))  => Ok(Value::Uint(i as u64)),
-				(Type::Char, Value::U32(i)) => Ok(Value::Char(i as u8 as char)),
-				(Type::Char, Value::Uint(i)) => Ok(Value::Char(i as u8 as char)),
-				(Type::String, Value::Char(c)) => Ok(Value::String(c.to_string())),
-				(typ, value) => panic!("tried casting {} to {:?}", value, typ),
+			Expr::Cast(e, typ) if e.is_untyped() => {
+				let typ = ctx.resolve_type(typ)?;
+				e.eval_as(ctx, &typ)?.cast(&typ)
 			}
-			*/
-			Expr::Not(e) => match e.eval(ctx)? {
+			Expr::Cast(e, typ) => e.eval(ctx)?.cast(&ctx.resolve_type(typ)?),
+			Expr::Not(e) => match e.eval_as(ctx, hint)? {
 				Value::Bool(b) => Ok(Value::Bool(!b)),
-				//Value::Uint(n) => Ok(Value::Uint(!n)),
-				Value::U32(n) => Ok(Value::U32(!n)),
-				val => unimplemented!(),
+				// same as two's complement, with infinitely many sign bits
+				Value::Int(n) => Ok(Value::Int(&-n - &BigInt::from(1))),
+				val if val.as_int().is_some() =>
+					Ok(Value::wrapping_int(!val.as_int().unwrap(), &val.get_type()).unwrap()),
+				val => Err(EvalError::TypeMismatch {
+					expected: Type::Bool,
+					got: val.get_type(),
+				})
+			}
+			
+			// negative number literals must fit in signed types
+			Expr::Neg(e) if hint.is_signed() && matches!(**e, Expr::Lit(Literal::Num(_))) => {
+				let Expr::Lit(Literal::Num(n)) = **e else { unreachable!() };
+				Value::exact_int(-(n as i128), hint)
+					.ok_or(EvalError::OutOfRange { value: -(n as i128), typ: hint.clone() })
 			}
 			
-			Expr::Neg(e) => match e.eval(ctx)? {
-				Value::U32(n) => Ok(Value::U32(n.wrapping_neg())),
+			Expr::Neg(e) => match e.eval_as(ctx, hint)? {
+				Value::Int(n) => Ok(Value::Int(-n)),
+				val if val.as_int().is_some() =>
+					Ok(Value::wrapping_int(-val.as_int().unwrap(), &val.get_type()).unwrap()),
 				val => Err(EvalError::TypeMismatch {
 					expected: Type::U32,
 					got: val.get_type(),
 				})
 			}
 			
-			Expr::App(fn_name, arg_exprs) => {
-				let mut args = Vec::new();
-				
-				for arg_expr in arg_exprs {
-					args.push(arg_expr.eval(ctx)?);
+			// variables holding a function, like closures passed as arguments,
+			// come before functions with the same name
+			Expr::App(fn_name, arg_exprs, span) => {
+				let (func, captured) = match ctx.get_var(fn_name) {
+					Some(Value::Fn(closure)) => (&closure.func, &closure.captured[..]),
+					_ => match ctx.items.find_fn(fn_name) {
+						Some(func) => (func, &[][..]),
+						None => Err(EvalError::UnknownIdent(fn_name.clone()))?,
+					}
+				};
+				if arg_exprs.len() != func.params.len() {
+					return Err(EvalError::ArgCount {
+						name: fn_name.clone(),
+						expected: func.params.len(),
+						got: arg_exprs.len(),
+						span: span.clone(),
+					});
 				}
 				
-				let mut func = None;
-				for f in &ctx.items.funcs {
-					if f.name == *fn_name {
-						func = Some(f);
-						break;
-					}
+				// untyped arguments take the type of their parameter
+				let mut args = Vec::new();
+				for (arg_expr, (_, typ)) in arg_exprs.iter().zip(&func.params) {
+					args.push(arg_expr.eval_as(ctx, &ctx.items.expand(typ)?)?);
 				}
 				
-				if let Some(f) = func {
-					f.apply(ctx, &args)
-				} else {
-					Err(EvalError::UnknownIdent(fn_name.clone()))
+				func.apply(&ctx.items, captured, args)
+			}
+			
+			// `and` and `or` short-circuit
+			Expr::BinOp(left, op @ (BinOp::And | BinOp::Or), right)
+			if !left.is_untyped() => {
+				let left = left.eval_as(ctx, hint)?;
+				
+				match (op, &left) {
+					(BinOp::And, Value::Bool(false)) => Ok(Value::Bool(false)),
+					(BinOp::Or, Value::Bool(true)) => Ok(Value::Bool(true)),
+					_ => {
+						let right = right.eval_as(ctx, &left.get_type())?;
+						op.apply(left, right)
+					}
 				}
 			}
 			
 			Expr::BinOp(left, op, right) => {
-				let left = left.eval(ctx)?;
-				let right = right.eval(ctx)?;
+				let (left, right) = match op {
+					// the type of the amount doesn't affect the result
+					BinOp::Exp | BinOp::Shl | BinOp::Shr | BinOp::Rol | BinOp::Ror =>
+						(left.eval_as(ctx, hint)?, right.eval(ctx)?),
+					// the result of a comparison says nothing about the
+					// types being compared
+					_ if op.is_relational() =>
+						eval_pair(ctx, left, right, &Type::Infer)?,
+					_ =>
+						eval_pair(ctx, left, right, hint)?,
+				};
 				
-				match (op, left, right) {
-					// 4
-					(BinOp::Exp, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l.pow(r as u32))),
-					
-					// 5
-					(BinOp::Mul, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l * r)),
-					(BinOp::Div, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l / r)),
-					(BinOp::Mod, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from((l % r + r) % r)),
-					(BinOp::And, Value::Bool(l), Value::Bool(r)) =>
-						Ok(Value::from(l && r)),
-					
-					// 6
-					(BinOp::Add, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l + r)),
-					(BinOp::Sub, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l - r)),
-					(BinOp::Or, Value::Bool(l), Value::Bool(r)) =>
-						Ok(Value::from(l || r)),
-					/*
-					(BinOp::Xor, Value::Bool(l), Value::Bool(r)) =>
-						Ok(Value::from(l ^ r)),
-					(BinOp::Xor, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l ^ r)),
-					*/
-					
-					// 7
-					(BinOp::Eq, l, r) =>
-						Ok(Value::from(l == r)),
-					(BinOp::Ne, l, r) =>
-						Ok(Value::from(l != r)),
-					(BinOp::Lt, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l < r)),
-					(BinOp::Gt, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l > r)),
-					(BinOp::Le, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l <= r)),
-					(BinOp::Ge, Value::U32(l), Value::U32(r)) =>
-						Ok(Value::from(l >= r)),
-					
-					
-					//(BinOp::Lt, Value::Uint(l), Value::Uint(r)) =>
-					//	Ok(Value::from(l < r)),
+				op.apply(left, right)
+			}
+			
+			// `a < b < c` is the same as `a < b and b < c`, except that `b` is
+			// only evaluated once.
+			Expr::Chain(first, rest) => {
+				let operands: Vec<&Expr> = std::iter::once(&**first)
+					.chain(rest.iter().map(|(_, e)| e))
+					.collect();
+				
+				// untyped operands get their type from the first typed one,
+				// which is evaluated ahead of time.
+				let typed = match operands.iter().position(|e| !e.is_untyped()) {
+					Some(i) => Some((i, operands[i].eval(ctx)?)),
+					None => None,
+				};
+				let hint = match &typed {
+					Some((_, val)) => val.get_type(),
+					None => Type::Infer,
+				};
+				let eval = |i: usize| match &typed {
+					Some((j, val)) if i == *j => Ok(val.clone()),
+					_ => operands[i].eval_as(ctx, &hint),
+				};
+				
+				let mut left = eval(0)?;
+				
+				for (i, (op, _)) in rest.iter().enumerate() {
+					let right = eval(i + 1)?;
 					
-					(op, left, right) =>
-						panic!(
-							"tried to do {:?} with types {:?} and {:?}: {:#?}",
-							op,
-							left.get_type(),
-							right.get_type(),
-							self
-						),
+					if op.apply(left, right.clone())? == Value::Bool(false) {
+						return Ok(Value::Bool(false));
+					}
+					left = right;
+				}
+				
+				Ok(Value::Bool(true))
+			}
+		}
+	}
+	
+	/// Checks whether this expression is made only of number literals (or
+	/// `nil`), and so can take on the type of whatever it's used with.
+	pub fn is_untyped(&self) -> bool {
+		match self {
+			Expr::Lit(Literal::Num(_) | Literal::Nil) => true,
+			Expr::Neg(e) | Expr::Not(e) => e.is_untyped(),
+			Expr::BinOp(l, op, r) if !op.is_relational() =>
+				l.is_untyped() && r.is_untyped(),
+			_ => false,
+		}
+	}
+}
+
+/// Evaluates two operands that should have the same type. If only one of them
+/// is untyped, the other is evaluated first so that its type can be used.
+fn eval_pair(ctx: &StackFrame, left: &Expr, right: &Expr, hint: &Type)
+-> EvalResult<(Value, Value)> {
+	if left.is_untyped() && !right.is_untyped() {
+		let right = right.eval_as(ctx, hint)?;
+		let left = left.eval_as(ctx, &right.get_type())?;
+		Ok((left, right))
+	} else {
+		let left = left.eval_as(ctx, hint)?;
+		let right = right.eval_as(ctx, &left.get_type())?;
+		Ok((left, right))
+	}
+}
+
+/// Moves a character by some number of code points, as long as it stays a
+/// valid unicode scalar value.
+pub fn offset_char(c: char, offset: i128) -> EvalResult<char> {
+	let n = c as i128 + offset;
+	u32::try_from(n).ok()
+		.and_then(char::from_u32)
+		.ok_or(EvalError::OutOfRange { value: n, typ: Type::Char })
+}
+
+impl BinOp {
+	/// Checks whether this operator compares its operands and gives a `Bool`.
+	pub fn is_relational(&self) -> bool {
+		matches!(self,
+			BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Gt | BinOp::Le | BinOp::Ge
+			| BinOp::Conj | BinOp::Disj
+		)
+	}
+	
+	pub fn apply(&self, left: Value, right: Value) -> EvalResult<Value> {
+		// fixed-size integers all work the same way
+		if let (Some(l), Some(r)) = (left.as_int(), right.as_int()) {
+			return self.apply_int(l, r, left.get_type(), right.get_type());
+		}
+		if let Value::Int(l) = &left {
+			return self.apply_big(l, right);
+		}
+		
+		match (self, left, right) {
+			// 5
+			(BinOp::And, Value::Bool(l), Value::Bool(r)) =>
+				Ok(Value::from(l && r)),
+			
+			// 6
+			(BinOp::Or, Value::Bool(l), Value::Bool(r)) =>
+				Ok(Value::from(l || r)),
+			(BinOp::Xor, Value::Bool(l), Value::Bool(r)) =>
+				Ok(Value::from(l ^ r)),
+			
+			// moving a character through the code points
+			(BinOp::Add, Value::Char(c), r) if r.as_int().is_some() =>
+				offset_char(c, r.as_int().unwrap()).map(Value::Char),
+			(BinOp::Sub, Value::Char(c), r) if r.as_int().is_some() =>
+				offset_char(c, -r.as_int().unwrap()).map(Value::Char),
+			
+			// 7
+			(BinOp::Lt, Value::Char(l), Value::Char(r)) =>
+				Ok(Value::from(l < r)),
+			(BinOp::Gt, Value::Char(l), Value::Char(r)) =>
+				Ok(Value::from(l > r)),
+			(BinOp::Le, Value::Char(l), Value::Char(r)) =>
+				Ok(Value::from(l <= r)),
+			(BinOp::Ge, Value::Char(l), Value::Char(r)) =>
+				Ok(Value::from(l >= r)),
+			(BinOp::Eq | BinOp::Ne, l, r) if l.get_type() != r.get_type() =>
+				Err(EvalError::TypeMismatch {
+					expected: l.get_type(),
+					got: r.get_type(),
+				}),
+			// 7
+			(BinOp::Eq, l, r) =>
+				Ok(Value::from(l == r)),
+			(BinOp::Ne, l, r) =>
+				Ok(Value::from(l != r)),
+			
+			// 8
+			(BinOp::Conj, Value::Bool(l), Value::Bool(r)) =>
+				Ok(Value::from(l & r)),
+			
+			// 9
+			(BinOp::Disj, Value::Bool(l), Value::Bool(r)) =>
+				Ok(Value::from(l | r)),
+			
+			//(BinOp::Lt, Value::Uint(l), Value::Uint(r)) =>
+			//	Ok(Value::from(l < r)),
+			
+			// like comparing booleans, as in `(a < b) < c`
+			(_, left, right) => Err(EvalError::TypeMismatch {
+				expected: left.get_type(),
+				got: right.get_type(),
+			}),
+		}
+	}
+	
+	/// Applies the operator to two fixed-size integers. Arithmetic wraps around
+	/// on overflow, the same way that the `+=` and `-=` statements do. Bitwise
+	/// operators can't wrap, or `^=` would stop being its own inverse, so a
+	/// result outside of an index type's range is an error.
+	fn apply_int(&self, l: i128, r: i128, ltype: Type, rtype: Type) -> EvalResult<Value> {
+		let wrap = |n| Ok(Value::wrapping_int(n, &ltype).unwrap());
+		let exact = |n| Value::exact_int(n, &ltype)
+			.ok_or(EvalError::OutOfRange { value: n, typ: ltype.clone() });
+		// index types have no bits to shift
+		let bits = ltype.bits().ok_or(EvalError::TypeMismatch {
+			expected: Type::U32,
+			got: ltype.clone(),
+		});
+		
+		// the amount to shift or raise by can be any kind of integer
+		let amount = u32::try_from(r)
+			.map_err(|_| EvalError::OutOfRange { value: r, typ: Type::U32 });
+		
+		match self {
+			BinOp::Exp => {
+				// reduce after every step, so index types stay in range
+				let reduce = |n: i128| Value::wrapping_int(n, &ltype).unwrap().as_int().unwrap();
+				let mut exp = amount?;
+				let mut base = l;
+				let mut acc = reduce(1);
+				while exp > 0 {
+					if exp % 2 == 1 {
+						acc = reduce(acc.wrapping_mul(base));
+					}
+					base = reduce(base.wrapping_mul(base));
+					exp /= 2;
 				}
+				return wrap(acc);
+			}
+			BinOp::Shl => {
+				let amount = amount?;
+				return if amount >= bits? {
+					wrap(0)
+				} else {
+					wrap((l as u128).wrapping_shl(amount) as i128)
+				};
+			}
+			// arithmetic shift for signed numbers, logical for unsigned
+			BinOp::Shr => {
+				bits?;
+				return wrap(l >> amount?.min(127));
+			}
+			BinOp::Rol | BinOp::Ror => {
+				let amount = if let BinOp::Rol = self { r } else { -r };
+				let val = Value::wrapping_int(l, &ltype).unwrap();
+				return Ok(val.rotate_left(amount).unwrap());
+			}
+			_ => {}
+		}
+		
+		if ltype != rtype {
+			return Err(EvalError::TypeMismatch {
+				expected: ltype,
+				got: rtype,
+			});
+		}
+		
+		match self {
+			// 5
+			BinOp::Mul => wrap(l.wrapping_mul(r)),
+			BinOp::Div if r == 0 => Err(EvalError::DivideByZero),
+			BinOp::Div => wrap(l / r),
+			BinOp::Mod if r == 0 => Err(EvalError::DivideByZero),
+			BinOp::Mod => wrap(l.rem_euclid(r)),
+			BinOp::And => exact(l & r),
+			
+			// 6
+			BinOp::Add => wrap(l + r),
+			BinOp::Sub => wrap(l - r),
+			BinOp::Or  => exact(l | r),
+			BinOp::Xor => exact(l ^ r),
+			
+			// 7
+			BinOp::Eq => Ok(Value::from(l == r)),
+			BinOp::Ne => Ok(Value::from(l != r)),
+			BinOp::Lt => Ok(Value::from(l < r)),
+			BinOp::Gt => Ok(Value::from(l > r)),
+			BinOp::Le => Ok(Value::from(l <= r)),
+			BinOp::Ge => Ok(Value::from(l >= r)),
+			
+			// `,` and `;` only join conditions
+			_ => Err(EvalError::TypeMismatch {
+				expected: Type::Bool,
+				got: ltype,
+			}),
+		}
+	}
+	
+	/// Applies the operator to a big integer. Nothing here can overflow.
+	fn apply_big(&self, l: &BigInt, right: Value) -> EvalResult<Value> {
+		// shifting is the same as multiplying or dividing by a power of 2
+		if let BinOp::Exp | BinOp::Shl | BinOp::Shr = self {
+			let r = match &right {
+				Value::Int(r) => r.to_i128(),
+				r => r.as_int(),
+			};
+			let amount = r.and_then(|r| u32::try_from(r).ok())
+				.ok_or(EvalError::OutOfRange {
+					value: r.unwrap_or(i128::MAX),
+					typ: Type::U32,
+				})?;
+			let pow2 = BigInt::from(2).pow(amount);
+			
+			return Ok(Value::Int(match self {
+				BinOp::Exp => l.pow(amount),
+				BinOp::Shl => l * &pow2,
+				// rounds down, like an arithmetic shift
+				_ => l.div_rem_euclid(&pow2).unwrap().0,
+			}));
+		}
+		
+		let r = match right {
+			Value::Int(r) => r,
+			r => return Err(EvalError::TypeMismatch {
+				expected: Type::Int,
+				got: r.get_type(),
+			}),
+		};
+		
+		Ok(match self {
+			// 5
+			BinOp::Mul => Value::Int(l * &r),
+			BinOp::Div => match l.div_rem(&r) {
+				Some((quot, _)) => Value::Int(quot),
+				None => Err(EvalError::DivideByZero)?,
+			}
+			BinOp::Mod => match l.div_rem_euclid(&r) {
+				Some((_, rem)) => Value::Int(rem),
+				None => Err(EvalError::DivideByZero)?,
 			}
+			
+			// 6
+			BinOp::Add => Value::Int(l + &r),
+			BinOp::Sub => Value::Int(l - &r),
+			
+			// 7
+			BinOp::Eq => Value::from(*l == r),
+			BinOp::Ne => Value::from(*l != r),
+			BinOp::Lt => Value::from(*l < r),
+			BinOp::Gt => Value::from(*l > r),
+			BinOp::Le => Value::from(*l <= r),
+			BinOp::Ge => Value::from(*l >= r),
+			
+			// there's no fixed number of bits to work with
+			_ => Err(EvalError::TypeMismatch {
+				expected: Type::U32,
+				got: Type::Int,
+			})?,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	// writes out the shape of an expression made of variables and operators
+	fn tree(expr: &Expr) -> String {
+		match expr {
+			Expr::LVal(lval) => lval.id.clone(),
+			Expr::BinOp(l, op, r) => format!("({} {:?} {})", tree(l), op, tree(r)),
+			_ => panic!("unexpected expression {:?}", expr),
 		}
 	}
+	
+	fn parse(source: &str) -> String {
+		tree(&Parser::new(source).parse_expr().unwrap())
+	}
+	
+	#[test]
+	fn shifts_bind_like_exponents() {
+		assert_eq!(parse("a + b << c"), "(a Add (b Shl c))");
+		assert_eq!(parse("a * b rol c"), "(a Mul (b Rol c))");
+		assert_eq!(parse("a << b ^ c"), "((a Shl b) Exp c)");
+		assert_eq!(parse("a ^ b >> c"), "((a Exp b) Shr c)");
+		assert_eq!(parse("a shl b shr c"), "((a Shl b) Shr c)");
+	}
+	
+	#[test]
+	fn exponents_group_right() {
+		assert_eq!(parse("a ^ b ^ c"), "(a Exp (b Exp c))");
+		assert_eq!(parse("a << b ^ c ^ d"), "((a Shl b) Exp (c Exp d))");
+		assert_eq!(parse("a ^ b ^ c ror d"), "((a Exp (b Exp c)) Ror d)");
+	}
 }
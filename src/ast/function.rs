@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use super::*;
 
 #[derive(Debug, Clone)]
@@ -8,6 +10,33 @@ pub struct Function {
     pub body: BlockExpr,
 }
 
+/// A function as a value, along with the values of any variables it uses from
+/// where it was made. Named functions don't capture anything.
+#[derive(Debug, Clone)]
+pub struct Closure {
+	pub func: Function,
+	pub captured: Vec<(String, Value)>,
+}
+
+// closures are the same if they come from the same function or function
+// literal, and captured the same values.
+impl PartialEq for Closure {
+	fn eq(&self, other: &Closure) -> bool {
+		self.func.name == other.func.name
+		&& self.func.get_type() == other.func.get_type()
+		&& self.captured == other.captured
+	}
+}
+
+impl Eq for Closure {}
+
+// functions can't have side effects, so tests count calls to check that nothing
+// is evaluated more often than it should be.
+#[cfg(test)]
+thread_local! {
+	pub static CALLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
 // param ::= ident [":" type]
 // params ::= [ param { "," param } [","] ]
 // fn ::= "fn" ident "(" params ")" ":" type
@@ -26,6 +55,67 @@ impl Parser<'_> {
 		};
 		self.next();
 		
+		let params = self.parse_fn_params()?;
+		
+		// get return type
+		self.expect(Token::Colon)
+			.ok_or("`:` after function parameters")?;
+			//.ok_or(FuncErr::NoRetType)?;
+		
+		let ret = self.parse_type()?;
+		
+		// choose parsing style based on next token
+		let body = match self.peek() {
+			// fn f(x): _
+			//     <block-expr>
+			Some(Token::Newline) => {
+				self.next();
+				
+				// the newline after the body ends the item
+				self.parse_block_expr()?
+			}
+			
+			// fn f(x): _ = <inline-expr>
+			Some(Token::Eq) => {
+				self.next();
+				
+				let body = self.parse_expr()?;
+				
+				if self.peek() != Some(&Token::Newline) {
+					Err("newline after function body")?;
+				}
+				
+				BlockExpr::Inline(body)
+			}
+			
+			_ => Err("`=` or newline after function declaration")?,
+		};
+		
+		Ok(Function { name: fn_name, params, body, ret })
+	}
+	
+	/// Parses a function literal, like `fn(x) = x + 1`. Its parameter and
+	/// return types are optional, since they can come from a function type.
+	pub fn parse_fn_literal(&mut self) -> ParseResult<Function> {
+		// function literals are named after where they're written
+		let name = format!("<fn at {}:{}>", self.line(), self.column());
+		self.expect(Token::Fn).ok_or("`fn`")?;
+		
+		let params = self.parse_fn_params()?;
+		
+		let ret = match self.expect(Token::Colon) {
+			Some(_) => self.parse_type()?,
+			None => Type::Infer,
+		};
+		
+		self.expect(Token::Eq)
+			.ok_or("`=` after function literal parameters")?;
+		
+		let body = BlockExpr::Inline(self.parse_expr()?);
+		Ok(Function { name, params, ret, body })
+	}
+	
+	fn parse_fn_params(&mut self) -> ParseResult<Vec<(String, Type)>> {
 		// parse parameter list
 		let mut params = Vec::new();
 		
@@ -69,50 +159,92 @@ impl Parser<'_> {
 		}
 		self.next();
 		
-		// get return type
-		self.expect(Token::Colon)
-			.ok_or("`:` after function parameters")?;
-			//.ok_or(FuncErr::NoRetType)?;
-		
-		let ret = self.parse_type()?;
-		
-		// choose parsing style based on next token
-		let body = match self.peek() {
-			// fn f(x): _
-			//     <block-expr>
-			Some(Token::Newline) => {
-				self.next();
-				
-				let body = self.parse_block_expr()?;
+		Ok(params)
+	}
+}
+
+
+impl Function {
+	/// The type of this function as a value. A function of several
+	/// parameters takes them as a tuple, like `(U32, U32) -> U32`.
+	pub fn get_type(&self) -> Type {
+		let arg = match &self.params[..] {
+			[(_, typ)] => typ.clone(),
+			params => Type::Tuple(params.iter().map(|(_, typ)| typ.clone()).collect()),
+		};
+		Type::Fn(Box::new(arg), Box::new(self.ret.clone()))
+	}
+	
+	/// Calls the function with the given arguments. The function body only
+	/// gets to see its parameters and whatever it captured, along with the
+	/// items around it, so it never sees the caller's variables. The caller
+	/// makes sure there's one argument for each parameter.
+	pub fn apply(&self, items: &Context, captured: &[(String, Value)], args: Vec<Value>) -> EvalResult<Value> {
+		#[cfg(test)]
+		CALLS.with(|calls| calls.set(calls.get() + 1));
 		
-				self.expect(Token::Newline)
-					.ok_or("newline after function body")?;
-				
-				body
+		for (arg, (_, typ)) in args.iter().zip(&self.params) {
+			let typ = items.expand(typ)?;
+			if typ != Type::Infer && arg.get_type() != typ {
+				return Err(EvalError::TypeMismatch {
+					expected: typ,
+					got: arg.get_type(),
+				});
 			}
-			
-			// fn f(x): _ = <inline-expr>
-			Some(Token::Eq) => {
-				self.next();
-				
-				let body = self.parse_expr()?;
-				
-				self.expect(Token::Newline)
-					.ok_or("newline after function body")?;
-				
-				BlockExpr::Inline(body)
-			}
-			
-			_ => Err("`=` or newline after function declaration")?,
-		};
+		}
 		
-		Ok(Function { name: fn_name, params, body, ret })
+		let mut frame = StackFrame::new(
+			items.clone(),
+			captured.iter().cloned()
+				.chain(self.params.iter()
+					.map(|(name, _)| name.clone())
+					.zip(args))
+				.collect(),
+		);
+		
+		let ret = items.expand(&self.ret)?;
+		let value = self.body.eval_as(&mut frame, &ret)?;
+		if ret != Type::Infer && value.get_type() != ret {
+			return Err(EvalError::TypeMismatch {
+				expected: self.ret.clone(),
+				got: value.get_type(),
+			});
+		}
+		Ok(value)
 	}
 }
 
-
-impl Function {
-	pub fn apply(&self, ctx: &StackFrame, args: &[Value]) -> EvalResult<Value> {
-		todo!()
+impl Closure {
+	/// Makes a closure from a function literal. Parameter and return types
+	/// that weren't written are taken from the hinted function type, and
+	/// variables the body uses are copied from the current stack frame.
+	pub fn new(func: &Function, ctx: &StackFrame, hint: &Type) -> Rc<Closure> {
+		let mut func = func.clone();
+		if let Type::Fn(arg, ret) = hint {
+			let arg_types = match (func.params.len(), &**arg) {
+				(1, arg) => vec![arg.clone()],
+				(n, Type::Tuple(types)) if types.len() == n => types.clone(),
+				_ => Vec::new(),
+			};
+			for ((_, typ), hint) in func.params.iter_mut().zip(arg_types) {
+				if *typ == Type::Infer {
+					*typ = hint;
+				}
+			}
+			if func.ret == Type::Infer {
+				func.ret = (**ret).clone();
+			}
+		}
+		
+		let mut captured: Vec<(String, Value)> = Vec::new();
+		for (name, value) in ctx.vars() {
+			if func.body.uses_var(name)
+			&& !func.params.iter().any(|(param, _)| param == name)
+			&& !captured.iter().any(|(var, _)| var == name) {
+				captured.push((name.clone(), value.clone()));
+			}
+		}
+		
+		Rc::new(Closure { func, captured })
 	}
 }
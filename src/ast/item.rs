@@ -2,12 +2,16 @@ use super::*;
 
 #[derive(Clone)]
 pub enum Item {
-	//Use(Path, Option<String>),
-	//Static(bool, String, Type, ConstExpr),
+	/// imports an item from a module, like `use io::Byte`, optionally under
+	/// another name. the path is kept as one string.
+	Use(String, Option<String>),
+	/// a named value, like `const WORD_BITS: U32 = 32`. it's evaluated once
+	/// when the program is loaded.
+	Const(String, Type, Expr),
 	Mod(Module),
 	Proc(Procedure),
 	Fn(Function),
-	//Type(Type),
+	Type(TypeDecl),
 }
 
 impl Item {
@@ -16,6 +20,10 @@ impl Item {
 			Item::Mod(m)  => &m.name,
 			Item::Proc(p) => &p.name,
 			Item::Fn(f)   => &f.name,
+			Item::Type(t) => &t.name,
+			Item::Const(name, ..) => name,
+			Item::Use(path, name) => name.as_deref()
+				.unwrap_or_else(|| path.rsplit("::").next().unwrap()),
 		}
 	}
 }
@@ -26,8 +34,48 @@ impl Parser<'_> {
 			Some(Token::Proc) => Item::Proc(self.parse_proc()?),
 			Some(Token::Mod)  => Item::Mod(self.parse_mod()?),
 			Some(Token::Fn)   => Item::Fn(self.parse_fn()?),
+			Some(Token::Struct | Token::Union | Token::Alias) =>
+				Item::Type(self.parse_type_decl()?),
 			
-			_ => Err("a module, function, or procedure")?,
+			// const NAME [: type] = expr
+			Some(Token::Const) => {
+				self.next();
+				let name = match self.peek() {
+					Some(Token::ConIdent) => self.slice().to_string(),
+					_ => Err("a constant name")?,
+				};
+				self.next();
+				
+				let typ = match self.expect(Token::Colon) {
+					Some(_) => self.parse_type()?,
+					None => Type::Infer,
+				};
+				
+				self.expect(Token::Eq)
+					.ok_or("`=` after constant name")?;
+				
+				Item::Const(name, typ, self.parse_expr()?)
+			}
+			
+			// use path [as Name]
+			Some(Token::Use) => {
+				self.next();
+				let path = self.parse_type_path()?;
+				
+				let name = match self.expect(Token::As) {
+					Some(_) if self.peek() == Some(&Token::ConIdent) => {
+						let name = self.slice().to_string();
+						self.next();
+						Some(name)
+					}
+					Some(_) => Err("a name after `as`")?,
+					None => None,
+				};
+				
+				Item::Use(path, name)
+			}
+			
+			_ => Err("a module, function, procedure, type, constant, or `use`")?,
 		};
 		
 		// mandatory newline (or EOF) after item
@@ -50,6 +98,10 @@ impl fmt::Debug for Item {
 			Item::Fn(f)   => f.fmt(fmt),
 			Item::Proc(p) => p.fmt(fmt),
 			Item::Mod(m)  => m.fmt(fmt),
+			Item::Type(t) => t.fmt(fmt),
+			Item::Const(name, typ, expr) =>
+				write!(fmt, "Const({:?}, {:?}, {:?})", name, typ, expr),
+			Item::Use(path, name) => write!(fmt, "Use({:?}, {:?})", path, name),
 		}
 	}
 }
@@ -1,17 +1,22 @@
-use std::num::ParseIntError;
+use std::num::{IntErrorKind, ParseIntError};
 
 use super::*;
 
 #[derive(Debug, Clone)]
 pub enum Literal {
 	Nil,
+	Bool(bool),
 	//Int(i64),
-	Num(u32),
+	Num(u64),
+	/// a number too big for a `u64`, which can only be an `Int`
+	BigNum(BigInt),
 	Char(char),
 	String(String),
 	Array(Vec<Expr>),
-	Variant(String),
-	//Fn(Vec<String>, Box<Expr>),
+	Tuple(Vec<Expr>),
+	Struct(String, Vec<(String, Expr)>),
+	Variant(String, Option<Box<Expr>>),
+	Fn(Box<Function>),
 }
 
 #[derive(Debug, Clone)]
@@ -54,26 +59,79 @@ impl From<ParseIntError> for LitErr {
 impl Parser<'_> {
 	pub fn parse_literal(&mut self) -> ParseResult<Literal> {
 		let lit = match self.peek() {
-			Some(Token::ConIdent) => Literal::Variant(self.slice().to_string()),
+			Some(Token::ConIdent) => match self.slice() {
+				"True" => Literal::Bool(true),
+				"False" => Literal::Bool(false),
+				name => {
+					let name = name.to_string();
+					self.next();
+					
+					match self.peek() {
+						// Name {field: value, ...}
+						Some(Token::LBrace) => {
+							self.next();
+							
+							let mut fields = Vec::new();
+							loop {
+								match self.peek() {
+									Some(Token::RBrace) => break,
+									Some(Token::VarIdent) => {
+										let field = self.slice().to_string();
+										self.next();
+										
+										self.expect(Token::Colon)
+											.ok_or("`:` after field name")?;
+										
+										fields.push((field, self.parse_expr()?));
+										
+										match self.peek() {
+											Some(Token::Comma) => { self.next(); }
+											Some(Token::RBrace) => {}
+											_ => Err("`,` or `}` after field in struct")?,
+										}
+									}
+									_ => Err("a field name or `}` in struct")?,
+								}
+							}
+							
+							Literal::Struct(name, fields)
+						}
+						
+						// Variant(value)
+						Some(Token::LParen) => {
+							self.next();
+							let value = self.parse_expr()?;
+							
+							if self.peek() != Some(&Token::RParen) {
+								Err("`)` after value of variant")?
+							}
+							
+							Literal::Variant(name, Some(Box::new(value)))
+						}
+						
+						// the name has already been consumed
+						_ => return Ok(Literal::Variant(name, None)),
+					}
+				}
+			}
+			
+			// the empty stack
+			Some(Token::Nil) => Literal::Nil,
 			
 			// binary form
 			Some(Token::Number) if self.slice().starts_with("0b") => {
-				let value = match u32::from_str_radix(&self.slice()[2..], 2) {
-					Ok(num) => num,
-					Err(_) => Err("malformed binary number literal")?,
-				};
-
-				Literal::Num(value)
+				match number(&self.slice()[2..], 2) {
+					Some(lit) => lit,
+					None => Err("malformed binary number literal")?,
+				}
 			}
 
 			// hexadecimal form
 			Some(Token::Number) if self.slice().starts_with("0x") => {
-				let value = match u32::from_str_radix(&self.slice()[2..], 16) {
-					Ok(num) => num,
-					Err(_) => Err("malformed hexadecimal number literal")?,
-				};
-
-				Literal::Num(value)
+				match number(&self.slice()[2..], 16) {
+					Some(lit) => lit,
+					None => Err("malformed hexadecimal number literal")?,
+				}
 			}
 
 			// bijective numeral form
@@ -109,17 +167,17 @@ impl Parser<'_> {
 				
 				let dec_repr: String = dec_repr.into_iter().collect();
 				
-				match u32::from_str_radix(&dec_repr, 10) {
-					Ok(n) => Literal::Num(n),
-					Err(_) => Err("malformed bijective numeral")?,
+				match number(&dec_repr, 10) {
+					Some(lit) => lit,
+					None => Err("malformed bijective numeral")?,
 				}
 			}
 				
 			// default: decimal form
 			Some(Token::Number) => {
-				match u32::from_str_radix(self.slice(), 10) {
-					Ok(value) => Literal::Num(value),
-					Err(_) => Err("malformed decimal number")?,
+				match number(self.slice(), 10) {
+					Some(lit) => lit,
+					None => Err("malformed decimal number")?,
 				}
 			}
 			
@@ -213,44 +271,8 @@ impl Parser<'_> {
 				Literal::Array(elements)
 			}
 			
-			// function/closure literal
-			/*
-			Some(Token::Fn) => {
-				self.next();
-				self.expect(Token::LParen)
-					.ok_or("`(` at start of closure")?;
-				
-				let mut args = Vec::new();
-				loop {
-					match self.peek() {
-						Some(Token::RParen) => break,
-						Some(Token::VarIdent) => {
-							let id = self.slice();
-							args.push(id.to_string());
-							self.next();
-							
-							match self.peek() {
-								Some(Token::Comma) => { self.next(); }
-								Some(Token::RParen) => {}
-								_ => Err("`,` or `)` after argument name in closure")?,
-							}
-						}
-						_ => Err("`,` or `)` after argument name in closure")?,
-					}
-				}
-				self.next();
-				
-				// TODO `:` with return type should be optional here
-				
-				self.expect(Token::Equal)
-					.ok_or("`=` after closure arguments")?;
-					//.ok_or(LitErr::
-				
-				let expr = self.parse_expr()?;
-				
-				Literal::Fn(args, Box::new(expr))
-			}
-			*/
+			// function/closure literal. its body has already been consumed.
+			Some(Token::Fn) => return Ok(Literal::Fn(Box::new(self.parse_fn_literal()?))),
 			
 			_ => Err("valid literal value")?
 		};
@@ -263,23 +285,164 @@ impl Parser<'_> {
 use crate::interpret::StackFrame;
 impl Literal {
 	pub fn eval(&self, ctx: &StackFrame) -> EvalResult<Value> {
+		self.eval_as(ctx, &Type::Infer)
+	}
+	
+	/// Evaluates the literal. Number literals take on the hinted type if it's
+	/// an integer type, and are a `U32` otherwise.
+	pub fn eval_as(&self, ctx: &StackFrame, hint: &Type) -> EvalResult<Value> {
 		Ok(match self {
-			Literal::Nil       => Value::Nil,
+			// the element type comes from the hint, or else the first push
+			Literal::Nil => match hint {
+				Type::Stack(elem) => Value::Stack(Vec::new(), (**elem).clone()),
+				_ => Value::Stack(Vec::new(), Type::Infer),
+			}
 			//Literal::Int(n)    => Value::U32(*n),
-			Literal::Num(n)    => Value::U32(*n as u32),
-			Literal::Char(c)   => Value::U32(*c as u32),
-			Literal::String(s) => Value::String(s.clone()),
+			Literal::Num(n) if *hint == Type::Int =>
+				Value::Int(BigInt::from(*n as i128)),
+			Literal::Num(n)    => {
+				let typ = if hint.is_int() { hint } else { &Type::U32 };
+				Value::exact_int(*n as i128, typ)
+					.ok_or(EvalError::OutOfRange {
+						value: *n as i128,
+						typ: typ.clone(),
+					})?
+			}
+			Literal::BigNum(n) if *hint == Type::Int || !hint.is_int() =>
+				Value::Int(n.clone()),
+			Literal::BigNum(_) => Err(EvalError::TypeMismatch {
+				expected: hint.clone(),
+				got: Type::Int,
+			})?,
+			Literal::Bool(b)   => Value::Bool(*b),
+			Literal::Char(c)   => Value::Char(*c),
+			Literal::String(s) => Value::String(Str::from(s.as_str())),
+			
+			// each element is hinted by its own type in the tuple type
+			Literal::Tuple(elems) => Value::Tuple({
+				let types = match hint {
+					Type::Tuple(types) if types.len() == elems.len() => types.clone(),
+					_ => vec![Type::Infer; elems.len()],
+				};
+				
+				elems.iter().zip(&types)
+					.map(|(expr, typ)| expr.eval_as(ctx, typ))
+					.collect::<EvalResult<_>>()?
+			}),
 			
+			// every element has to be the same type. the hint for the
+			// elements comes from the array type, or else the first element.
 			Literal::Array(arr) => Value::Array({
+				let mut elem_type = match hint {
+					Type::Fn(_, elem) => (**elem).clone(),
+					_ => Type::Infer,
+				};
+				
 				let mut vec = Vec::with_capacity(arr.len());
 				for expr in arr.iter() {
-					vec.push(expr.eval(ctx)?);
+					let value = expr.eval_as(ctx, &elem_type)?;
+					if elem_type == Type::Infer {
+						elem_type = value.get_type();
+					} else if value.get_type() != elem_type {
+						return Err(EvalError::TypeMismatch {
+							expected: elem_type,
+							got: value.get_type(),
+						});
+					}
+					vec.push(value);
 				}
 				vec.into_boxed_slice()
 			}),
 			
-			Literal::Variant(_) => unimplemented!(),
-			//Literal::Fn(args, ret) => todo!(),
+			// fields are checked against the declared struct, and their values
+			// decide what any type parameters are
+			Literal::Struct(name, fields) => {
+				let decl = ctx.items.find_type(name)?;
+				let mut bindings = decl.bindings_from(hint);
+				let decl_fields = match &decl.kind {
+					DeclKind::Struct(decl_fields) => decl_fields.as_slice(),
+					_ => &[],
+				};
+				
+				if let Some((field, _)) = fields.iter()
+					.find(|(field, _)| decl_fields.iter().all(|(f, _)| f != field))
+				{
+					return Err(EvalError::NoField {
+						typ: decl.instance(&bindings),
+						field: field.clone(),
+					});
+				}
+				
+				let mut values = Vec::with_capacity(decl_fields.len());
+				for (field, field_type) in decl_fields {
+					let field_type = &ctx.items.expand(field_type)?;
+					let expr = match fields.iter().find(|(f, _)| f == field) {
+						Some((_, expr)) => expr,
+						None => Err(EvalError::MissingField {
+							typ: decl.instance(&bindings),
+							field: field.clone(),
+						})?,
+					};
+					
+					let value = expr.eval_as(ctx, &field_type.substitute(&bindings))?;
+					if !field_type.unify(&value.get_type(), &mut bindings) {
+						return Err(EvalError::TypeMismatch {
+							expected: field_type.substitute(&bindings),
+							got: value.get_type(),
+						});
+					}
+					values.push((field.clone(), value));
+				}
+				
+				Value::Struct(decl.instance(&bindings), values)
+			}
+			
+			// bare names are constants, if there's one by that name
+			Literal::Variant(name, None) if ctx.items.find_const(name).is_some() =>
+				ctx.items.find_const(name).unwrap().clone(),
+			
+			// the hint decides which union a variant belongs to, or else
+			// the first union with a variant of that name
+			Literal::Variant(name, value) => {
+				let decl = match hint {
+					Type::Decl(union, _) => ctx.items.find_type(union).ok()
+						.filter(|decl| decl.variant(name).is_some()),
+					_ => None,
+				};
+				let decl = decl
+					.or_else(|| ctx.items.types.iter().find(|decl| decl.variant(name).is_some()))
+					.ok_or(EvalError::UnknownIdent(name.clone()))?;
+				let mut bindings = decl.bindings_from(hint);
+				
+				let typ = match decl.variant(name).unwrap() {
+					Some(typ) => Some(ctx.items.expand(typ)?),
+					None => None,
+				};
+				let value = match (typ, value) {
+					(Some(typ), Some(expr)) => {
+						let value = expr.eval_as(ctx, &typ.substitute(&bindings))?;
+						if !typ.unify(&value.get_type(), &mut bindings) {
+							return Err(EvalError::TypeMismatch {
+								expected: typ.substitute(&bindings),
+								got: value.get_type(),
+							});
+						}
+						Some(Box::new(value))
+					}
+					(None, None) => None,
+					(Some(typ), None) => Err(EvalError::TypeMismatch {
+						expected: typ.substitute(&bindings),
+						got: Type::Nil,
+					})?,
+					(None, Some(expr)) => Err(EvalError::TypeMismatch {
+						expected: Type::Nil,
+						got: expr.eval(ctx)?.get_type(),
+					})?,
+				};
+				
+				Value::Union(decl.instance(&bindings), name.clone(), value)
+			}
+			Literal::Fn(func) => Value::Fn(Closure::new(func, ctx, hint)),
 		})
 	}
 }
@@ -289,12 +452,26 @@ impl Literal {
 		match self {
 			//Literal::Int(_)    => Some(Type::Int),
 			Literal::Nil       => None,
+			Literal::Bool(_)   => Some(Type::Bool),
 			Literal::Num(_)    => Some(Type::U32),
-			Literal::Char(_)   => Some(Type::U32),
+			Literal::BigNum(_) => Some(Type::Int),
+			Literal::Char(_)   => Some(Type::Char),
 			Literal::String(_) => Some(Type::String),
 			Literal::Array(v)  => None,
-			Literal::Variant(_) => None,
-			//Literal::Fn(..)    => None,
+			Literal::Tuple(_)  => None,
+			Literal::Struct(..) => None,
+			Literal::Variant(..) => None,
+			Literal::Fn(..)    => None,
 		}
 	}
 }
+
+// numbers that don't fit in a `u64` are kept whole, instead of failing
+fn number(digits: &str, radix: u32) -> Option<Literal> {
+	match u64::from_str_radix(digits, radix) {
+		Ok(n) => Some(Literal::Num(n)),
+		Err(e) if *e.kind() == IntErrorKind::PosOverflow =>
+			BigInt::from_str_radix(digits, radix).map(Literal::BigNum),
+		Err(_) => None,
+	}
+}
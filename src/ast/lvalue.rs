@@ -62,6 +62,7 @@ impl Parser<'_> {
 		// get lval name
 		let id = match self.peek() {
 			Some(Token::VarIdent) => self.slice().to_string(),
+			Some(Token::ConIdent) => Err("a variable name, since constants can't be modified")?,
 			_ => Err("variable name in left-value expression")?,
 			//_ => return Err(LValErr::Name),
 		};
@@ -83,10 +84,19 @@ impl Parser<'_> {
 					self.next();
 					Some(name)
 				}
+				// tuple elements, like `.0`
+				Some(Token::Number) => {
+					end_span = self.span();
+					match self.parse_literal()? {
+						Literal::Num(n) => Some(n.to_string()),
+						_ => unreachable!(),
+					}
+				}
 				_ => None,
 			};
 			
 			// ( args, )
+			let mut slice = false;
 			let args = if self.peek() == Some(&Token::LParen) {
 				let mut args = Vec::new();
 				self.next();
@@ -101,11 +111,21 @@ impl Parser<'_> {
 							match self.peek() {
 								Some(Token::Comma) => { self.next(); }
 								Some(Token::RParen) => {}
-								_ => todo!()
+								// .(start..end)
+								Some(Token::Range) if name.is_none() && args.len() == 1 => {
+									self.next();
+									args.push(self.parse_expr()?);
+									slice = true;
+									
+									if self.peek() != Some(&Token::RParen) {
+										Err("`)` after end of slice")?
+									}
+								}
+								_ => Err("`,` or `)` after index")?,
 							}
 						}
 						
-						None => todo!(),
+						None => Err("`)` after index")?,
 					}
 				}
 				end_span = self.span();
@@ -116,6 +136,8 @@ impl Parser<'_> {
 				None
 			};
 			
+			// slices are shorthand for the `.slice(start, end)` method
+			let name = if slice { Some("slice".to_string()) } else { name };
 			ops.push(Deref { name, args });
 		}
 		
@@ -126,6 +148,18 @@ impl Parser<'_> {
 	}
 }
 
+impl LValue {
+	/// Checks whether the variable with the given name is used anywhere in
+	/// this l-value, including in any index expressions.
+	pub fn uses_var(&self, name: &str) -> bool {
+		self.id == name
+		|| self.ops.iter()
+			.filter_map(|op| op.args.as_ref())
+			.flatten()
+			.any(|arg| arg.uses_var(name))
+	}
+}
+
 use crate::interpret::StackFrame;
 impl LValue {
 	pub fn eval(&self, ctx: &StackFrame) -> EvalResult<Value> {
@@ -39,6 +39,8 @@ impl fmt::Debug for ProcDef {
 #[derive(Debug, Clone)]
 pub struct Procedure {
 	pub name: String,
+	/// names of the type parameters of a generic procedure
+	pub type_params: Vec<String>,
 	pub params: Vec<Param>,
 	/// How and where a procedure is defined.
 	pub code: ProcDef,
@@ -54,6 +56,10 @@ impl Parser<'_> {
 		};
 		self.next();
 		
+		// [T, U]
+		let type_params = self.parse_type_params()?;
+		self.type_params = type_params.clone();
+		
 		let mut params = Vec::new();
 		
 		// parse parameter list
@@ -85,6 +91,12 @@ impl Parser<'_> {
 						// parameter's type
 						let typ = self.parse_type()?;
 						
+						// procedures can't be modified, so they can only be
+						// passed as `const`
+						if matches!(typ, Type::Proc(_)) && !constant {
+							Err("`const` before a procedure parameter")?;
+						}
+						
 						params.push(Param { constant, name: param_name, typ });
 						
 						match self.peek() {
@@ -115,9 +127,11 @@ impl Parser<'_> {
 			}
 		}
 		self.next();
+		self.type_params.clear();
 		
 		Ok(Procedure {
 			name: proc_name,
+			type_params,
 			params,
 			code: ProcDef::User(code),
 		})
@@ -128,15 +142,34 @@ impl Parser<'_> {
 use crate::interpret::{Context, StackFrame};
 
 impl Procedure {
+	/// The type of this procedure as a value, like `proc(var U32)`.
+	pub fn get_type(&self) -> Type {
+		Type::Proc(
+			self.params.iter()
+				.map(|param| (!param.constant, param.typ.clone()))
+				.collect(),
+		)
+	}
+	
 	fn call_base(&self, items: Context, dir: Dir, args: Vec<Value>) -> EvalResult<Vec<Value>> {
-		// verify number of arguments and their types
+		// verify number of arguments and their types. type parameters of
+		// generic procedures are bound to whatever the arguments have, and
+		// have to be the same everywhere they're used.
 		assert_eq!(args.len(), self.params.len(),
 			"wrong number of parameters before calling proc {}", self.name
 		);
-		for (arg, param) in args.iter().zip(&self.params) {
-			assert_eq!(arg.get_type(), param.typ,
-				"value with wrong type passed before calling proc {}", self.name
-			);
+		let param_types = self.params.iter()
+			.map(|param| items.expand(&param.typ))
+			.collect::<EvalResult<Vec<_>>>()?;
+		let mut type_args = Vec::new();
+		for ((arg, param), typ) in args.iter().zip(&self.params).zip(&param_types) {
+			// intrinsics like `push` leave their parameter types to be inferred
+			if !typ.unify(&arg.get_type(), &mut type_args) {
+				return Err(EvalError::TypeMismatch {
+					expected: param.typ.substitute(&type_args),
+					got: arg.get_type(),
+				});
+			}
 		}
 		
 		// make stack frame with parameter names bound to argument values
@@ -147,6 +180,7 @@ impl Procedure {
 				.zip(args.clone())
 				.collect(),
 		);
+		ctx.type_args = type_args.clone();
 		
 		// execute the actual code
 		match (dir, &self.code) {
@@ -156,7 +190,7 @@ impl Procedure {
 				}
 			}
 			(Dir::Back, ProcDef::User(code)) => {
-				for stmt in code {
+				for stmt in code.iter().rev() {
 					stmt.clone().invert().eval(&mut ctx)?;
 				}
 			}
@@ -175,10 +209,13 @@ impl Procedure {
 		assert_eq!(args.len(), self.params.len(),
 			"wrong number of parameters after calling proc {}", self.name
 		);
-		for (arg, param) in args.iter().zip(&self.params) {
-			assert_eq!(arg.get_type(), param.typ,
-				"value with wrong type received after calling proc {}", self.name
-			);
+		for ((arg, param), typ) in args.iter().zip(&self.params).zip(&param_types) {
+			if !typ.unify(&arg.get_type(), &mut type_args) {
+				return Err(EvalError::TypeMismatch {
+					expected: param.typ.substitute(&type_args),
+					got: arg.get_type(),
+				});
+			}
 		}
 		
 		Ok(args)
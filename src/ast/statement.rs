@@ -1,11 +1,13 @@
+use std::ops::Range;
+
 use super::*;
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
 	Skip,
 	
-	//Not(LValue),
-	//Neg(LValue),
+	Not(LValue),
+	Neg(LValue),
 	
 	RotLeft(LValue, Expr),
 	RotRight(LValue, Expr),
@@ -13,17 +15,25 @@ pub enum Stmt {
 	Xor(LValue, Expr),
 	Add(LValue, Expr),
 	Sub(LValue, Expr),
+	Mul(LValue, Expr),
+	Div(LValue, Expr),
 	
 	Swap(LValue, LValue),
-	//CSwap(Factor, LValue, LValue),
+	CSwap(Expr, LValue, LValue),
+	
+	/// runs a statement on a tuple put together from some lvalues, like the
+	/// `(q, r)` in `(q, r) += div_mod(n, d)`, then assigns its elements back to
+	/// them. the tuple is a variable named after how the lvalues were written.
+	Destructure(String, Vec<LValue>, Box<Self>),
 	
-	Do(String, Vec<Expr>),
-	Undo(String, Vec<Expr>),
+	/// the procedure's name, its arguments, and where the name was written
+	Do(String, Vec<Expr>, Range<usize>),
+	Undo(String, Vec<Expr>, Range<usize>),
 	
 	Var(String, Type, Expr, Vec<Self>, Expr),
 	If(Expr, Vec<Self>, Vec<Self>, Expr),
 	From(Expr, Vec<Self>, Vec<Self>, Expr),
-	//FromVar(String, Expr, Vec<Stmt>, Vec<Stmt>, Expr),
+	FromVar(String, Expr, Vec<Self>, Vec<Self>, Expr),
 	//Match(String, Vec<_, Vec<Stmt>>),
 	//For(String, Expr, Vec<Stmt>),
 }
@@ -32,8 +42,11 @@ impl Stmt {
 	pub fn invert(self) -> Self {
 		match self {
 			Stmt::Skip     => self,
+			Stmt::Not(..)  => self,
+			Stmt::Neg(..)  => self,
 			Stmt::Xor(..)  => self,
 			Stmt::Swap(..) => self,
+			Stmt::CSwap(..) => self,
 			
 			Stmt::RotLeft(l, v) => Stmt::RotRight(l, v),
 			Stmt::RotRight(l, v) => Stmt::RotLeft(l, v),
@@ -41,31 +54,45 @@ impl Stmt {
 			Stmt::Add(l, v) => Stmt::Sub(l, v),
 			Stmt::Sub(l, v) => Stmt::Add(l, v),
 			
-			Stmt::Do(p, args) => Stmt::Undo(p, args),
-			Stmt::Undo(p, args) => Stmt::Do(p, args),
+			Stmt::Mul(l, v) => Stmt::Div(l, v),
+			Stmt::Div(l, v) => Stmt::Mul(l, v),
+			
+			Stmt::Do(p, args, span) => Stmt::Undo(p, args, span),
+			Stmt::Undo(p, args, span) => Stmt::Do(p, args, span),
+			
+			Stmt::Destructure(name, lvals, stmt) =>
+				Stmt::Destructure(name, lvals, Box::new(stmt.invert())),
 			
-			// recursively invert blocks
+			// recursively invert blocks, which also run in reverse order
 			Stmt::Var(name, ty, init, scope, dest) =>
 				Stmt::Var(
 					name, ty,
 					dest,
-					scope.into_iter().map(|s| s.invert()).collect(),
+					scope.into_iter().rev().map(|s| s.invert()).collect(),
 					init
 				),
 			Stmt::If(test, main_block, else_block, assert) =>
 				Stmt::If(
 					assert,
-					main_block.into_iter().map(|s| s.invert()).collect(),
-					else_block.into_iter().map(|s| s.invert()).collect(),
+					main_block.into_iter().rev().map(|s| s.invert()).collect(),
+					else_block.into_iter().rev().map(|s| s.invert()).collect(),
 					test
 				),
 			Stmt::From(assert, block, loop_block, test) =>
 				Stmt::From(
 					test,
-					block.into_iter().map(|s| s.invert()).collect(),
-					loop_block.into_iter().map(|s| s.invert()).collect(),
+					block.into_iter().rev().map(|s| s.invert()).collect(),
+					loop_block.into_iter().rev().map(|s| s.invert()).collect(),
 					assert
 				),
+			Stmt::FromVar(name, init, block, loop_block, end) =>
+				Stmt::FromVar(
+					name,
+					end,
+					block.into_iter().rev().map(|s| s.invert()).collect(),
+					loop_block.into_iter().rev().map(|s| s.invert()).collect(),
+					init
+				),
 		}
 	}
 }
@@ -82,21 +109,55 @@ impl Parser<'_> {
 				Stmt::Skip
 			}
 			
+			// unary statements
+			Token::Not => {
+				self.next();
+				Stmt::Not(self.parse_lval()?)
+			}
+			Token::Minus => {
+				self.next();
+				Stmt::Neg(self.parse_lval()?)
+			}
+			
+			// conditional swap
+			Token::CSwap => {
+				self.next();
+				
+				let cond = self.parse_expr()?;
+				self.expect(Token::Comma)
+					.ok_or("`,` after `cswap` condition")?;
+				
+				let left = self.parse_lval()?;
+				self.expect(Token::Comma)
+					.ok_or("`,` between swapped values")?;
+				
+				let right = self.parse_lval()?;
+				
+				// the condition must still hold after swapping, otherwise the
+				// statement can't be undone.
+				if cond.uses_var(&left.id) || cond.uses_var(&right.id) {
+					Err("`cswap` condition that doesn't use the swapped values")?;
+				}
+				
+				Stmt::CSwap(cond, left, right)
+			}
+			
 			/* do-call and undo-call syntax accept three forms:
-			   + `do something`
-			   + `do something: some, args` (1 arg min) TODO
+			   + `do something {some, args}` (0 arg min)
+			   + `do something: some, args` (1 arg min)
 			   + `do something(
 			          multiline,
 			          args
-			      )` (0 arg min) TODO
-			   also has special syntax like:
+			      )` (0 arg min)
+			   `do something` on its own is the same as passing no arguments.
+			   TODO also have special syntax like:
 			   + do something: var new_var, drop used_var
 			*/
 			kw @ Token::Do | kw @ Token::Undo => {
 				self.next();
 				
-				let name = match self.peek() {
-					Some(Token::VarIdent) => self.slice().to_string(),
+				let (name, span) = match self.peek() {
+					Some(Token::VarIdent) => (self.slice().to_string(), self.span()),
 					_ => Err(match kw {
 						Token::Do => "procedure name after `do`",
 						Token::Undo => "procedure name after `undo`",
@@ -105,61 +166,49 @@ impl Parser<'_> {
 				};
 				self.next();
 				
-				// TODO check for parentheses. if so, go into multiline mode
-				let mut args = Vec::new();
-				
-				if self.peek() == Some(&Token::Newline) {
+				let args = match self.peek() {
 					// do nothing on final newline
-				}
-				/*else if self.peek() == Some(&Token::Colon) {
-					self.next();
-					// TODO check for newline in case expression was forgotten
-					let expr = self.parse_expr()?;
-					args.push(expr);
+					Some(Token::Newline) | None => Vec::new(),
 					
-					loop {
-						match self.peek() {
-							Some(Token::Newline) | None =>
-								break,
-							Some(Token::Comma) => {
-								self.next();
-								// TODO check for "substatements" first.
-								// E.g. `var file` or `drop buf` in args.
-								args.push(self.parse_expr()?);
-							}
-							_ => Err("`,` or newline")?,
+					Some(Token::Colon) => {
+						self.next();
+						
+						if self.peek() == Some(&Token::Newline) {
+							Err("an argument after `:`")?;
 						}
-					}
-				}*/
-				else if self.peek() == Some(&Token::LBrace) {
-					self.next();
-					
-					loop {
-						match self.peek() {
-							Some(Token::RBrace) => break,
-							Some(_) => {
-								args.push(self.parse_expr()?);
-								
-								match self.peek() {
-									Some(Token::Comma | Token::Newline) => {
-										self.next();
-									}
-									Some(Token::RBrace) => {}
-									_ => Err("`,`, `}`, or newline")?,
+						
+						let mut args = vec![self.parse_expr()?];
+						loop {
+							match self.peek() {
+								Some(Token::Newline) | None => break,
+								Some(Token::Comma) => {
+									self.next();
+									// TODO check for "substatements" first.
+									// E.g. `var file` or `drop buf` in args.
+									args.push(self.parse_expr()?);
 								}
+								_ => Err("`,` or newline")?,
 							}
-							None => Err("`}` or expression")?,
 						}
+						args
 					}
 					
-					self.next();
-				} else {
-					return Err("`{`, or newline")?;
-				}
+					Some(Token::LBrace) => {
+						self.next();
+						self.parse_call_args(Token::RBrace)?
+					}
+					
+					Some(Token::LParen) => {
+						self.next();
+						self.parse_call_args(Token::RParen)?
+					}
+					
+					_ => Err("`{`, `(`, `:`, or newline")?,
+				};
 				
 				match kw {
-					Token::Do   => Stmt::Do(name, args),
-					Token::Undo => Stmt::Undo(name, args),
+					Token::Do   => Stmt::Do(name, args, span),
+					Token::Undo => Stmt::Undo(name, args, span),
 					_ => unreachable!()
 				}
 			}
@@ -168,8 +217,28 @@ impl Parser<'_> {
 			Token::From => {
 				self.next();
 				
+				// `from var i := 0` declares the loop variable, and uses its
+				// initial value as the loop assertion
+				let decl = if self.expect(Token::Var).is_some() {
+					let name = match self.peek() {
+						Some(Token::VarIdent) => self.slice().to_string(),
+						_ => Err("name of loop variable")?,
+					};
+					self.next();
+					
+					self.expect(Token::Assign)
+						.ok_or("`:=` in loop variable declaration")?;
+					
+					Some((name, self.parse_expr()?))
+				} else {
+					None
+				};
+				
 				// parse loop assertion
-				let assert = self.parse_expr()?;
+				let assert = match decl {
+					Some(_) => None,
+					None => Some(self.parse_cond()?),
+				};
 				
 				self.expect(Token::Newline)
 					.ok_or("newline after `from` assertion")?;
@@ -189,7 +258,17 @@ impl Parser<'_> {
 				self.next();
 				
 				// parse the `until` test expression
-				let test = self.parse_expr()?;
+				let test = self.parse_cond()?;
+				
+				// with a loop variable, the test has to give its final value
+				// so that it can be dropped afterwards
+				let end = match (&decl, &test) {
+					(None, _) => None,
+					(Some((name, _)), Expr::BinOp(var, BinOp::Eq, end))
+					if matches!(&**var, Expr::LVal(lval) if lval.id == *name && lval.ops.is_empty()) =>
+						Some((**end).clone()),
+					(Some(_), _) => Err("`until` test of the form `<loop variable> = <value>`")?,
+				};
 				
 				self.expect(Token::Newline)
 					.ok_or("newline after `until` expression")?;
@@ -207,7 +286,13 @@ impl Parser<'_> {
 				}
 				self.next();
 				
-				Stmt::From(assert, main_block, back_block, test)
+				match (decl, assert, end) {
+					(Some((name, init)), _, Some(end)) =>
+						Stmt::FromVar(name, init, main_block, back_block, end),
+					(None, Some(assert), _) =>
+						Stmt::From(assert, main_block, back_block, test),
+					_ => unreachable!(),
+				}
 			}
 			
 			// var-drop
@@ -276,7 +361,7 @@ impl Parser<'_> {
 				self.next();
 				
 				// parse if condition
-				let cond = self.parse_expr()?;
+				let cond = self.parse_cond()?;
 				
 				self.expect(Token::Newline)
 					.ok_or("newline after `if` predicate")?;
@@ -323,17 +408,19 @@ impl Parser<'_> {
 				// parse `fi` assertion, if any
 				let assert = match self.peek() {
 					Some(Token::Newline) => cond.clone(),
-					Some(_) => self.parse_expr()?,
+					Some(_) => self.parse_cond()?,
 					None => Err("a newline or expression after `fi`")?,
 				};
 				
 				Stmt::If(cond, main_block, else_block, assert)
 			}
 			
-			Token::VarIdent => {
-				let lval = self.parse_lval()?;
+			// constants are rejected by `parse_lval`
+			Token::VarIdent | Token::ConIdent | Token::LParen => {
+				let (lval, pattern) = self.parse_assignee()?;
+				let name = lval.id.clone();
 				
-				match self.peek().ok_or("modifying operator")? {
+				let stmt = match self.peek().ok_or("modifying operator")? {
 					Token::Assign => {
 						self.next();
 						let expr = self.parse_expr()?;
@@ -349,6 +436,16 @@ impl Parser<'_> {
 						let expr = self.parse_expr()?;
 					    Stmt::Sub(lval, expr)
 					}
+					Token::MulAssign => {
+						self.next();
+						let expr = self.parse_expr()?;
+					    Stmt::Mul(lval, expr)
+					}
+					Token::DivAssign => {
+						self.next();
+						let expr = self.parse_expr()?;
+					    Stmt::Div(lval, expr)
+					}
 					
 					Token::RolAssign => {
 						self.next();
@@ -363,11 +460,22 @@ impl Parser<'_> {
 					
 					Token::Swap => {
 						self.next();
-						let rhs = self.parse_lval()?;
-					    Stmt::Swap(lval, rhs)
+						let (rhs, rhs_pattern) = self.parse_assignee()?;
+						match rhs_pattern {
+							Some(lvals) => Stmt::Destructure(
+								rhs.id.clone(), lvals,
+								Box::new(Stmt::Swap(lval, rhs)),
+							),
+							None => Stmt::Swap(lval, rhs),
+						}
 					}
 					
-					_ => Err("`:=`, `+=`, `-=`, or `<>`")?,
+					_ => Err("`:=`, `+=`, `-=`, `*=`, `/=`, or `<>`")?,
+				};
+				
+				match pattern {
+					Some(lvals) => Stmt::Destructure(name, lvals, Box::new(stmt)),
+					None => stmt,
 				}
 			}
 			
@@ -389,109 +497,398 @@ impl Parser<'_> {
 	}
 }
 
+impl Parser<'_> {
+	/// Parses the lvalue that an assignment modifies, or a tuple of lvalues
+	/// like `(q, r)`. A tuple is stood in for by a variable named after it,
+	/// which is returned along with the lvalues it's made of.
+	fn parse_assignee(&mut self) -> ParseResult<(LValue, Option<Vec<LValue>>)> {
+		if self.peek() != Some(&Token::LParen) {
+			return Ok((self.parse_lval()?, None));
+		}
+		let start = self.span().start;
+		self.next();
+		
+		let mut lvals = Vec::new();
+		loop {
+			lvals.push(self.parse_lval()?);
+			
+			match self.peek() {
+				Some(Token::Comma) => { self.next(); }
+				Some(Token::RParen) => break,
+				_ => Err("`,` or `)` in tuple of variables")?,
+			}
+		}
+		let span = start..self.span().end;
+		self.next();
+		
+		// the name can't be a valid identifier, so it can't shadow anything
+		let name = self.lexer.source()[span.clone()].to_string();
+		Ok((LValue { id: name, ops: Vec::new(), span }, Some(lvals)))
+	}
+	
+	/// Parses a list of arguments for a procedure call, up to and including
+	/// the closing token. Arguments may be separated by commas, newlines, or
+	/// both, so that long argument lists can span multiple lines.
+	fn parse_call_args(&mut self, close: Token) -> ParseResult<Vec<Expr>> {
+		let mut args = Vec::new();
+		self.skip_newlines();
+		
+		loop {
+			match self.peek() {
+				Some(tok) if *tok == close => break,
+				Some(_) => {
+					args.push(self.parse_expr()?);
+					
+					match self.peek() {
+						Some(Token::Comma) => {
+							self.next();
+							self.skip_newlines();
+						}
+						Some(Token::Newline) => self.skip_newlines(),
+						Some(tok) if *tok == close => {}
+						_ => Err("`,`, newline, or end of argument list")?,
+					}
+				}
+				None => Err("end of argument list or expression")?,
+			}
+		}
+		self.next();
+		
+		Ok(args)
+	}
+}
+
 use crate::interpret::StackFrame;
 impl Stmt {
+	// the lvalue that an assignment statement modifies.
+	fn assigned(&self) -> Option<&LValue> {
+		match self {
+			Stmt::Not(lval) | Stmt::Neg(lval)
+			| Stmt::RotLeft(lval, _) | Stmt::RotRight(lval, _)
+			| Stmt::Xor(lval, _) | Stmt::Add(lval, _) | Stmt::Sub(lval, _)
+			| Stmt::Mul(lval, _) | Stmt::Div(lval, _) => Some(lval),
+			_ => None,
+		}
+	}
+	
+	// the value an assignment statement modifies its lvalue by. rotations
+	// aren't included, since their amount isn't the same type as the lvalue.
+	fn operand(&self) -> Option<&Expr> {
+		match self {
+			Stmt::Xor(_, expr) | Stmt::Add(_, expr) | Stmt::Sub(_, expr)
+			| Stmt::Mul(_, expr) | Stmt::Div(_, expr) => Some(expr),
+			_ => None,
+		}
+	}
+	
+	fn operand_mut(&mut self) -> Option<&mut Expr> {
+		match self {
+			Stmt::Xor(_, expr) | Stmt::Add(_, expr) | Stmt::Sub(_, expr)
+			| Stmt::Mul(_, expr) | Stmt::Div(_, expr) => Some(expr),
+			_ => None,
+		}
+	}
+	
+	fn assigned_mut(&mut self) -> Option<&mut LValue> {
+		match self {
+			Stmt::Not(lval) | Stmt::Neg(lval)
+			| Stmt::RotLeft(lval, _) | Stmt::RotRight(lval, _)
+			| Stmt::Xor(lval, _) | Stmt::Add(lval, _) | Stmt::Sub(lval, _)
+			| Stmt::Mul(lval, _) | Stmt::Div(lval, _) => Some(lval),
+			_ => None,
+		}
+	}
+	
+	// tuples are modified one element at a time, by the matching element of
+	// the value. the value is evaluated once, and its elements are handed out
+	// through a variable whose name can't shadow anything.
+	fn eval_elements(&self, ctx: &mut StackFrame, lval: &LValue, types: Vec<Type>) -> EvalResult<Value> {
+		let len = types.len();
+		let typ = Type::Tuple(types);
+		let value = match self.operand() {
+			Some(expr) => {
+				let value = expr.eval_as(ctx, &typ)?;
+				if value.get_type() != typ {
+					return Err(EvalError::TypeMismatch {
+						expected: typ,
+						got: value.get_type(),
+					});
+				}
+				Some(value)
+			}
+			None => None,
+		};
+		let element = |i: usize| Deref { name: Some(i.to_string()), args: None };
+		
+		let has_value = value.is_some();
+		if let Some(value) = value {
+			ctx.push("()".to_string(), value);
+		}
+		let mut result = Ok(Value::Nil);
+		for i in 0..len {
+			let mut stmt = self.clone();
+			stmt.assigned_mut().unwrap().ops.push(element(i));
+			if let Some(expr) = stmt.operand_mut() {
+				*expr = Expr::LVal(LValue {
+					id: "()".to_string(),
+					ops: vec![element(i)],
+					span: lval.span.clone(),
+				});
+			}
+			
+			result = stmt.eval(ctx);
+			if result.is_err() {
+				break;
+			}
+		}
+		if has_value {
+			ctx.pop();
+		}
+		result
+	}
+	
 	pub fn eval(&self, ctx: &mut StackFrame) -> EvalResult<Value> {
+		// characters in a string can't be borrowed through `get_mut`, so the
+		// statement is run on a copy of the character, which is then put back.
+		// the copy's name is empty so it can't shadow anything.
+		if let Some(lval) = self.assigned() {
+			if let Some((string_path, i)) = ctx.string_index(lval)? {
+				let mut stmt = self.clone();
+				*stmt.assigned_mut().unwrap() = LValue {
+					id: String::new(),
+					ops: Vec::new(),
+					span: lval.span.clone(),
+				};
+				
+				ctx.push(String::new(), ctx.get(lval)?);
+				let result = stmt.eval(ctx);
+				let (_, c) = ctx.pop().unwrap();
+				result?;
+				ctx.set_char(&string_path, i, c)?;
+				return Ok(Value::Nil);
+			}
+			
+			if let Type::Tuple(types) = ctx.get(lval)?.get_type() {
+				return self.eval_elements(ctx, lval, types);
+			}
+		}
+		
 		match self {
 			Stmt::Skip => {}
 			
-			Stmt::Var(id, _, init, block, dest) => {
-				let init = init.eval(ctx)?;
+			Stmt::Var(id, typ, init, block, dest) => {
+				// errors show the type as it was written, aliases and all
+				let declared = typ.substitute(&ctx.type_args);
+				let typ = &ctx.resolve_type(typ)?;
+				let init = init.eval_as(ctx, typ)?;
+				if *typ != Type::Infer && init.get_type() != *typ {
+					return Err(EvalError::TypeMismatch {
+						expected: declared,
+						got: init.get_type(),
+					});
+				}
 				ctx.push(id.clone(), init);
 				
 				for stmt in block {
-					if let Err(e) = stmt.eval(ctx) {
-						eprintln!("{:?}", stmt);
-						panic!("var {}: {:?}", id, e);
-					}
+					stmt.eval(ctx)?;
 				}
 				
 				let (final_id, final_val) = ctx.pop().unwrap();
-				
-				assert_eq!(*id, final_id);
-				assert_eq!(final_val, dest.eval(ctx)?,
-					"variable {:?} had unexpected value", id);
+				if final_id != *id || final_val != dest.eval_as(ctx, &final_val.get_type())? {
+					return Err(EvalError::IrreversibleState);
+				}
+			}
+			
+			Stmt::Not(lval) => match ctx.get_mut(lval)? {
+				Value::Bool(b) => *b = !*b,
+				Value::Int(n) => *n = &-n.clone() - &BigInt::from(1),
+				val if val.as_int().is_some() => {
+					let n = val.as_int().unwrap();
+					*val = Value::wrapping_int(!n, &val.get_type()).unwrap();
+				}
+				val => Err(EvalError::TypeMismatch {
+					expected: Type::U32,
+					got: val.get_type(),
+				})?
+			}
+			
+			Stmt::Neg(lval) => match ctx.get_mut(lval)? {
+				// a single bit is its own two's complement
+				Value::Bool(_) => {}
+				Value::Int(n) => *n = -n.clone(),
+				val if val.as_int().is_some() => {
+					let n = val.as_int().unwrap();
+					*val = Value::wrapping_int(-n, &val.get_type()).unwrap();
+				}
+				val => Err(EvalError::TypeMismatch {
+					expected: Type::U32,
+					got: val.get_type(),
+				})?
 			}
 			
 			Stmt::Xor(lval, expr) => {
-				let expr = expr.eval(ctx)?;
-				match (ctx.get_mut(&lval)?, &expr) {
-					(Value::U32(l), Value::U32(r)) =>
-						*l ^= *r,
-					_ => panic!("tried to do something illegal")
+				let typ = ctx.get(lval)?.get_type();
+				let expr = expr.eval_as(ctx, &typ)?;
+				match ctx.get_mut(lval)? {
+					Value::Bool(l) => match expr {
+						Value::Bool(r) => *l ^= r,
+						r => Err(EvalError::TypeMismatch {
+							expected: Type::Bool,
+							got: r.get_type(),
+						})?
+					}
+					l if l.as_int().is_some() =>
+						*l = BinOp::Xor.apply(l.clone(), expr)?,
+					l => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: l.get_type(),
+					})?
 				}
 			}
 			
 			Stmt::Add(lval, expr) => {
-				let expr = expr.eval(ctx)?;
-				match (ctx.get_mut(&lval)?, &expr) {
-					(Value::U32(l), Value::U32(r)) =>
-						*l = l.wrapping_add(*r),
+				let typ = ctx.get(lval)?.get_type();
+				let expr = expr.eval_as(ctx, &typ)?;
+				match (ctx.get_mut(lval)?, expr) {
 					(Value::String(l), Value::String(r)) =>
-						*l += r,
-					(l, r) => panic!(
-						"tried to increment a {:?} with a {:?}",
-						l, r
-					)
+						l.push_str(r.as_str()),
+					(Value::Int(l), Value::Int(r)) =>
+						*l = &*l + &r,
+					(Value::Char(c), r) if r.as_int().is_some() =>
+						*c = expression::offset_char(*c, r.as_int().unwrap())?,
+					(l, r) if l.as_int().is_some() =>
+						*l = BinOp::Add.apply(l.clone(), r)?,
+					(l, r) => Err(EvalError::TypeMismatch {
+						expected: l.get_type(),
+						got: r.get_type(),
+					})?
 				}
 			}
 			
 			Stmt::Sub(lval, expr) => {
-				let expr = expr.eval(ctx)?;
-				match (ctx.get_mut(&lval)?, &expr) {
-					(Value::U32(l), Value::U32(r)) =>
-						*l = l.wrapping_sub(*r),
-					(Value::String(l), Value::String(r)) => {
-						*l = match l.strip_suffix(r) {
-							//"string {:?} does not end with {:?}",
+				let typ = ctx.get(lval)?.get_type();
+				let expr = expr.eval_as(ctx, &typ)?;
+				match (ctx.get_mut(lval)?, expr) {
+					(Value::String(l), Value::String(r)) =>
+						if !l.strip_suffix(r.as_str()) {
+							Err(EvalError::IrreversibleState)?
+						}
+					(Value::Int(l), Value::Int(r)) =>
+						*l = &*l - &r,
+					(Value::Char(c), r) if r.as_int().is_some() =>
+						*c = expression::offset_char(*c, -r.as_int().unwrap())?,
+					(l, r) if l.as_int().is_some() =>
+						*l = BinOp::Sub.apply(l.clone(), r)?,
+					(l, r) => Err(EvalError::TypeMismatch {
+						expected: l.get_type(),
+						got: r.get_type(),
+					})?
+				}
+			}
+			
+			// multiplication by an odd number is a bijection modulo 2^n, so
+			// anything else would lose information. big integers only lose
+			// information when multiplied by 0.
+			Stmt::Mul(lval, expr) => {
+				let typ = ctx.get(lval)?.get_type();
+				let expr = expr.eval_as(ctx, &typ)?;
+				match (ctx.get_mut(lval)?, expr) {
+					(Value::Int(_), Value::Int(r)) if r.is_zero() =>
+						Err(EvalError::IrreversibleState)?,
+					(Value::Int(l), Value::Int(r)) =>
+						*l = &*l * &r,
+					// modulo N, only numbers coprime to N can be undone
+					(Value::Index(l, size), Value::Index(r, r_size)) if *size == r_size =>
+						match index_inverse(r, *size) {
+							Some(_) => *l = (*l as u64 * r as u64 % *size as u64) as u32,
 							None => Err(EvalError::IrreversibleState)?,
-							Some(s) => s.into(),
-						};
+						}
+					(l, r) if l.as_int().is_some() && r.as_int().is_some() => {
+						if r.as_int().unwrap() % 2 == 0 {
+							Err(EvalError::IrreversibleState)?
+						}
+						*l = BinOp::Mul.apply(l.clone(), r)?;
 					}
-					(l, r) => panic!(
-						"tried to decrement a {:?} with a {:?}",
-						l.get_type(), r.get_type()
-					)
+					(l, r) => Err(EvalError::TypeMismatch {
+						expected: l.get_type(),
+						got: r.get_type(),
+					})?
+				}
+			}
+			
+			// the inverse of a wrapping multiplication is a multiplication by
+			// the modular inverse of the divisor. when the division is exact,
+			// this gives the same result as a normal division. big integers
+			// can only be divided exactly.
+			Stmt::Div(lval, expr) => {
+				let typ = ctx.get(lval)?.get_type();
+				let expr = expr.eval_as(ctx, &typ)?;
+				match (ctx.get_mut(lval)?, expr) {
+					(Value::Int(l), Value::Int(r)) => match l.div_rem(&r) {
+						Some((quot, rem)) if rem.is_zero() => *l = quot,
+						_ => Err(EvalError::IrreversibleState)?,
+					}
+					(Value::Index(l, size), Value::Index(r, r_size)) if *size == r_size =>
+						match index_inverse(r, *size) {
+							Some(inv) => *l = (*l as u64 * inv as u64 % *size as u64) as u32,
+							None => Err(EvalError::IrreversibleState)?,
+						}
+					(l, r) if l.as_int().is_some() && r.as_int().is_some() => {
+						let n = r.as_int().unwrap();
+						if n % 2 == 0 {
+							Err(EvalError::IrreversibleState)?
+						}
+						// an inverse modulo 2^64 is also one modulo 2^n, n < 64
+						let inv = mod_inverse(n as u64) as i128;
+						let inv = Value::wrapping_int(inv, &r.get_type()).unwrap();
+						*l = BinOp::Mul.apply(l.clone(), inv)?;
+					}
+					(l, r) => Err(EvalError::TypeMismatch {
+						expected: l.get_type(),
+						got: r.get_type(),
+					})?
 				}
 			}
 			
 			Stmt::RotLeft(lval, expr) => {
 				let expr = expr.eval(ctx)?;
-				match (ctx.get_mut(&lval)?, &expr) {
-					(Value::U32(l), Value::U32(r)) =>
-						*l = l.rotate_left(*r as u32),
-					(Value::String(s), Value::U32(rot_amt)) => {
-						// copy the substring that will wrap around
-						let left_str = s[..*rot_amt as usize].to_string();
-						// remove substring from original string
-						*s = s[*rot_amt as usize..].to_string();
-						// put left substring on the right
-						s.push_str(&left_str);
-					}
-					_ => panic!("tried to do something illegal")
+				match (ctx.get_mut(lval)?, expr.as_int()) {
+					(l, Some(r)) if l.as_int().is_some() =>
+						*l = l.rotate_left(r).unwrap(),
+					(Value::String(s), Some(rot_amt)) =>
+						s.rotate_left(rot_amt),
+					(_, None) => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: expr.get_type(),
+					})?,
+					(l, _) => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: l.get_type(),
+					})?
 				}
 			}
 			
 			Stmt::RotRight(lval, expr) => {
 				let expr = expr.eval(ctx)?;
-				match (ctx.get_mut(&lval)?, &expr) {
-					(Value::U32(l), Value::U32(r)) =>
-						*l = l.rotate_right(*r as u32),
-					(Value::String(s), Value::U32(rot_amt)) => {
-						// calculate length to be preserved
-						let len = s.len() - *rot_amt as usize;
-						// chop off the right substring to wrap around
-						let right_str = s.split_off(len);
-						// insert substring at the start
-						s.insert_str(0, &right_str);
-					}
-					_ => panic!("tried to do something illegal")
+				match (ctx.get_mut(lval)?, expr.as_int()) {
+					(l, Some(r)) if l.as_int().is_some() =>
+						*l = l.rotate_left(-r).unwrap(),
+					(Value::String(s), Some(rot_amt)) =>
+						s.rotate_left(-rot_amt),
+					(_, None) => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: expr.get_type(),
+					})?,
+					(l, _) => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: l.get_type(),
+					})?
 				}
 			}
 			
 			// sighhhhhhhhhhhhhhhhh
 			Stmt::Swap(left, right) => {
-				ctx.swap(&left.id, &right.id)?
+				ctx.swap(left, right)?
 				/*
 				// ensure types are the same
 				assert_eq!(
@@ -514,93 +911,155 @@ impl Stmt {
 				*/
 			}
 			
+			Stmt::CSwap(cond, left, right) => match cond.eval(ctx)? {
+				Value::Bool(true) => ctx.swap(left, right)?,
+				Value::Bool(false) => {}
+				val => Err(EvalError::TypeMismatch {
+					expected: Type::Bool,
+					got: val.get_type(),
+				})?
+			}
+			
+			Stmt::Destructure(name, lvals, stmt) => {
+				let elems = lvals.iter()
+					.map(|lval| ctx.get(lval))
+					.collect::<EvalResult<_>>()?;
+				
+				ctx.push(name.clone(), Value::Tuple(elems));
+				let result = stmt.eval(ctx);
+				let (_, tuple) = ctx.pop().unwrap();
+				result?;
+				
+				match tuple {
+					Value::Tuple(elems) => for (lval, elem) in lvals.iter().zip(elems) {
+						ctx.set(lval, elem)?;
+					}
+					_ => unreachable!(),
+				}
+			}
+			
 			/* Clearly we need more info here. Eventually we'll need to store
 			the "path" of the current module with the procedure, but for now
 			just having the items of the current module is good enough. So find
 			a way to make that available. */
-			kw @ Stmt::Do(callee_name, args)
-			| kw @ Stmt::Undo(callee_name, args) => {
-				let mut vals = Vec::new();
-				for arg in args {
-					vals.push(arg.eval(ctx)?);
-				}
+			kw @ Stmt::Do(callee_name, args, span)
+			| kw @ Stmt::Undo(callee_name, args, span) => {
+				// variables holding a procedure, like a `const f: proc(var U32)`
+				// parameter, come before procedures with the same name
+				let callee_name = match ctx.get_var(callee_name) {
+					Some(Value::Proc(name, _)) => name,
+					_ => callee_name,
+				};
 				
 				// search items in current module for a matching procedure
-				let mut proc = None;
-				for p in &ctx.items.procs {
-					if p.name == *callee_name {
-						proc = Some(p);
-						break;
-					}
+				let proc = ctx.items.find_proc(callee_name)
+					.ok_or_else(|| EvalError::UnknownIdent(callee_name.clone()))?
+					.clone();
+				if args.len() != proc.params.len() {
+					return Err(EvalError::ArgCount {
+						name: callee_name.clone(),
+						expected: proc.params.len(),
+						got: args.len(),
+						span: span.clone(),
+					});
 				}
 				
-				// if procedure name found, call it. otherwise panic.
-				let results = match (kw, proc) {
-					(Stmt::Do(..), Some(pr)) => pr.call(ctx.items.clone(), vals)?,
-					(Stmt::Undo(..), Some(pr)) => pr.uncall(ctx.items.clone(), vals)?,
-					_ => panic!("could not (un)call procedure {}: not found", callee_name),
+				// untyped arguments take the type of their parameter
+				let mut vals = Vec::new();
+				for (arg, param) in args.iter().zip(&proc.params) {
+					vals.push(arg.eval_as(ctx, &ctx.items.expand(&param.typ)?)?);
+				}
+				
+				let results = match kw {
+					Stmt::Do(..) => proc.call(ctx.items.clone(), vals)?,
+					_ => proc.uncall(ctx.items.clone(), vals)?,
 				};
 				
 				// update variables to new values
-				for (arg_expr, result) in args.iter().zip(results.into_iter()) {
+				for ((arg_expr, result), param) in args.iter().zip(results).zip(&proc.params) {
 					// check for arguments that were just an l-value, then
-					// update those.
-					let var_value = match arg_expr {
-						Expr::LVal(lval) => ctx.get_mut(&lval)?,
-						_ => continue,
-					};
-					
+					// update those. `const` parameters are never copied back.
 					// usually we'd do type checking here, but Procedure::call
 					// already handles that.
-					*var_value = result;
+					if param.constant {
+						continue;
+					}
+					if let Expr::LVal(lval) = arg_expr {
+						ctx.set(lval, result)?;
+					}
 				}
 			}
 			
 			Stmt::If(test, block, else_block, assert) => {
-				match test.eval(ctx)? {
-					Value::Bool(true) => {
-						for stmt in block {
-							if let Err(e) = stmt.eval(ctx) {
-								eprintln!("{:?}", stmt);
-								panic!("{:?}", e);
-							}
-						}
-						assert_eq!(assert.eval(ctx)?, Value::Bool(true));
+				let taken = eval_bool(test, ctx)?;
+				for stmt in if taken { block } else { else_block } {
+					stmt.eval(ctx)?;
+				}
+				
+				// the assertion picks the branch to undo, so it has to agree
+				if eval_bool(assert, ctx)? != taken {
+					return Err(EvalError::IrreversibleState);
+				}
+			}
+			
+			Stmt::From(assert, do_block, loop_block, test) => {
+				if !eval_bool(assert, ctx)? {
+					return Err(EvalError::IrreversibleState);
+				}
+				loop {
+					for stmt in do_block {
+						stmt.eval(ctx)?;
 					}
-					Value::Bool(false) => {
-						for stmt in else_block {
-							if let Err(e) = stmt.eval(ctx) {
-								eprintln!("{:?}", stmt);
-								panic!("{:?}", e);
-							}
-						}
-						assert_eq!(assert.eval(ctx)?, Value::Bool(false));
+					
+					if eval_bool(test, ctx)? {
+						break;
+					}
+					for stmt in loop_block {
+						stmt.eval(ctx)?;
+					}
+					
+					// only the start of the loop may satisfy the assertion
+					if eval_bool(assert, ctx)? {
+						return Err(EvalError::IrreversibleState);
 					}
-					_ => panic!("tried to do something illegal")
 				}
 			}
 			
-			Stmt::From(assert, do_block, loop_block, test) => {
-				assert_eq!(assert.eval(ctx)?, Value::Bool(true));
+			// same as a `from` loop inside of a `var` block, where the loop
+			// assertion is `name = init` and the test is `name = end`.
+			Stmt::FromVar(name, init, do_block, loop_block, end) => {
+				// an untyped start value takes the type of the end value
+				let init_val = match init.is_untyped() {
+					true => init.eval_as(ctx, &end.eval(ctx)?.get_type())?,
+					false => init.eval(ctx)?,
+				};
+				let typ = init_val.get_type();
+				ctx.push(name.clone(), init_val);
+				let var = LValue { id: name.clone(), ops: Vec::new(), span: 0..0 };
+				
 				loop {
 					for stmt in do_block {
 						stmt.eval(ctx)?;
 					}
 					
-					match test.eval(ctx)? {
-						Value::Bool(true) => break,
-						Value::Bool(false) =>
-							for stmt in loop_block {
-								if let Err(e) = stmt.eval(ctx) {
-									eprintln!("{:?}", stmt);
-									panic!("{:?}", e);
-								}
-							}
-						_ => panic!("tried to do something illegal")
+					if ctx.get(&var)? == end.eval_as(ctx, &typ)? {
+						break;
 					}
 					
-					//eprintln!("{:?}", t);
-					assert_eq!(assert.eval(ctx)?, Value::Bool(false));
+					for stmt in loop_block {
+						stmt.eval(ctx)?;
+					}
+					
+					// coming back to the start would make the loop ambiguous
+					// when running backwards
+					if ctx.get(&var)? == init.eval_as(ctx, &typ)? {
+						return Err(EvalError::IrreversibleState);
+					}
+				}
+				
+				let (final_id, final_val) = ctx.pop().unwrap();
+				if final_id != *name || final_val != end.eval_as(ctx, &typ)? {
+					return Err(EvalError::IrreversibleState);
 				}
 			}
 		}
@@ -608,3 +1067,81 @@ impl Stmt {
 		Ok(Value::Nil)
 	}
 }
+
+/// Evaluates the test or assertion of an `if` or `from`, which has to be a
+/// `Bool`.
+fn eval_bool(expr: &Expr, ctx: &mut StackFrame) -> EvalResult<bool> {
+	match expr.eval(ctx)? {
+		Value::Bool(b) => Ok(b),
+		val => Err(EvalError::TypeMismatch {
+			expected: Type::Bool,
+			got: val.get_type(),
+		}),
+	}
+}
+
+/// Calculates the multiplicative inverse of an odd number modulo 2^64.
+fn mod_inverse(n: u64) -> u64 {
+	debug_assert!(n % 2 == 1);
+	// newton's method; each iteration doubles the number of correct bits.
+	let mut inv = n;
+	for _ in 0..6 {
+		inv = inv.wrapping_mul(2u64.wrapping_sub(n.wrapping_mul(inv)));
+	}
+	inv
+}
+
+/// Calculates the multiplicative inverse of a number modulo the size of an
+/// index type, if there is one.
+fn index_inverse(n: u32, size: u32) -> Option<u32> {
+	// extended euclidean algorithm
+	let (mut old_r, mut r) = (n as i64, size as i64);
+	let (mut old_s, mut s) = (1i64, 0i64);
+	while r != 0 {
+		let quot = old_r / r;
+		(old_r, r) = (r, old_r - quot * r);
+		(old_s, s) = (s, old_s - quot * s);
+	}
+	
+	(old_r == 1).then(|| old_s.rem_euclid(size as i64) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn mod_inverse_odd() {
+		for n in [1, 3, 7, 255, 0x6db6db6db6db6db7, u64::MAX] {
+			assert_eq!(n.wrapping_mul(mod_inverse(n)), 1);
+		}
+		assert_eq!(mod_inverse(7), 0x6db6db6db6db6db7);
+	}
+	
+	// an inverse modulo 2^64 works for the smaller sizes too
+	#[test]
+	fn mod_inverse_narrow() {
+		let inv = mod_inverse(3);
+		assert_eq!(3u64.wrapping_mul(inv) as u32, 1);
+		assert_eq!(3u64.wrapping_mul(inv) as u8, 1);
+	}
+	
+	#[test]
+	fn index_inverse_units() {
+		assert_eq!(index_inverse(7, 10), Some(3));
+		assert_eq!(index_inverse(3, 7), Some(5));
+		assert_eq!(index_inverse(1, 1), Some(0));
+		for n in 1..12 {
+			if let Some(inv) = index_inverse(n, 12) {
+				assert_eq!(n * inv % 12, 1);
+			}
+		}
+	}
+	
+	#[test]
+	fn index_inverse_non_units() {
+		assert_eq!(index_inverse(5, 10), None);
+		assert_eq!(index_inverse(0, 7), None);
+		assert_eq!(index_inverse(6, 12), None);
+	}
+}
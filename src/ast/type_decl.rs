@@ -0,0 +1,151 @@
+use super::*;
+
+/// The fields of a struct, the variants of a union, or the type that an alias
+/// stands for.
+#[derive(Debug, Clone)]
+pub enum DeclKind {
+	/// named fields, which all hold a value at the same time.
+	Struct(Vec<(String, Type)>),
+	/// named variants, only one of which is held at a time. variants can
+	/// optionally carry a value.
+	Union(Vec<(String, Option<Type>)>),
+	/// another name for an existing type. it's interchangeable with the type
+	/// it stands for.
+	Alias(Type),
+}
+
+/// A user-defined type, which may be generic over some type parameters.
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+	pub name: String,
+	pub params: Vec<String>,
+	pub kind: DeclKind,
+}
+
+// type-decl ::= "struct" Name [params] "{" field ":" type { "," ... } "}"
+//           ::= "union" Name [params] "{" Variant [":" type] { "," ... } "}"
+//           ::= "alias" Name [params] "=" type
+impl Parser<'_> {
+	pub fn parse_type_decl(&mut self) -> ParseResult<TypeDecl> {
+		let keyword = self.next();
+		
+		let name = match self.peek() {
+			Some(Token::ConIdent) => self.slice().to_string(),
+			_ => Err("type name")?,
+		};
+		self.next();
+		
+		let params = self.parse_type_params()?;
+		self.type_params = params.clone();
+		
+		let is_struct = match keyword {
+			Some(Token::Struct) => true,
+			Some(Token::Union) => false,
+			Some(Token::Alias) => {
+				self.expect(Token::Eq)
+					.ok_or("`=` after alias name")?;
+				
+				let typ = self.parse_type()?;
+				self.type_params.clear();
+				
+				return Ok(TypeDecl { name, params, kind: DeclKind::Alias(typ) });
+			}
+			_ => Err("`struct`, `union`, or `alias`")?,
+		};
+		
+		self.expect(Token::LBrace)
+			.ok_or("`{` after type name")?;
+		
+		let mut fields = Vec::new();
+		let mut variants = Vec::new();
+		loop {
+			self.skip_newlines();
+			
+			match self.peek() {
+				Some(Token::RBrace) => break,
+				
+				Some(Token::VarIdent) if is_struct => {
+					let field = self.slice().to_string();
+					self.next();
+					
+					self.expect(Token::Colon)
+						.ok_or("`:` after field name")?;
+					
+					fields.push((field, self.parse_type()?));
+				}
+				Some(Token::ConIdent) if !is_struct => {
+					let variant = self.slice().to_string();
+					self.next();
+					
+					// the variant's value is optional
+					let typ = match self.expect(Token::Colon) {
+						Some(_) => Some(self.parse_type()?),
+						None => None,
+					};
+					
+					variants.push((variant, typ));
+				}
+				
+				_ if is_struct => Err("a field name")?,
+				_ => Err("a variant name")?,
+			}
+			
+			self.skip_newlines();
+			match self.peek() {
+				Some(Token::Comma) => { self.next(); }
+				Some(Token::RBrace) => {}
+				_ => Err("`,` or `}` in type declaration")?,
+			}
+		}
+		self.next();
+		self.type_params.clear();
+		
+		let kind = if is_struct {
+			DeclKind::Struct(fields)
+		} else {
+			DeclKind::Union(variants)
+		};
+		
+		Ok(TypeDecl { name, params, kind })
+	}
+}
+
+impl TypeDecl {
+	/// Binds the type parameters to the types given in a hint, if the hint is
+	/// this same type. Anything else is left to be inferred.
+	pub fn bindings_from(&self, hint: &Type) -> Vec<(String, Type)> {
+		match hint {
+			Type::Decl(name, args) if *name == self.name && args.len() == self.params.len() =>
+				self.params.iter().cloned()
+					.zip(args.iter().cloned())
+					.filter(|(_, arg)| *arg != Type::Infer)
+					.collect(),
+			_ => Vec::new(),
+		}
+	}
+	
+	/// The type of a value of this type, given what its parameters are bound
+	/// to. Parameters that were never bound are left to be inferred.
+	pub fn instance(&self, bindings: &[(String, Type)]) -> Type {
+		Type::Decl(
+			self.name.clone(),
+			self.params.iter()
+				.map(|param| match Type::Param(param.clone()).substitute(bindings) {
+					Type::Param(_) => Type::Infer,
+					typ => typ,
+				})
+				.collect(),
+		)
+	}
+	
+	/// Gets the type of the value carried by a variant, if this is a union with
+	/// that variant.
+	pub fn variant(&self, name: &str) -> Option<&Option<Type>> {
+		match &self.kind {
+			DeclKind::Union(variants) => variants.iter()
+				.find(|(variant, _)| variant == name)
+				.map(|(_, typ)| typ),
+			_ => None,
+		}
+	}
+}
@@ -17,9 +17,24 @@ pub enum Type {
 	
 	Nil,
 	
+	/// true or false
+	Bool,
+	
+	/// signed integers of a fixed size, in two's complement
+	I8, I16, I32, I64,
+	
+	/// unsigned integers of a fixed size
+	U8, U16, U64,
+	
 	/// 32-bit unsigned integer, default number type
 	U32,
 	
+	/// signed integer of any size, which never overflows
+	Int,
+	
+	/// a unicode scalar value
+	Char,
+	
 	/// resizeable utf-8 string type
 	String,
 	
@@ -32,6 +47,10 @@ pub enum Type {
 	/// boolean type), etc.
 	Index(u32),
 	
+	/// an index type whose size is given by a constant, like `0WORD_BITS`.
+	/// it's replaced by the `Index` type of that size once constants are known.
+	IndexOf(String),
+	
 	Stack(Box<Self>),
 	
 	/// a type that takes a value of the first type and returns a value of the
@@ -41,7 +60,213 @@ pub enum Type {
 	/// string. you can also do `03 -> u32` for an array of three `u32`s.
 	Fn(Box<Self>, Box<Self>),
 	
-	//Decl(String, Vec<Self>),
+	/// a procedure, given whether each of its parameters can be modified
+	/// (`var`) and its type. for example, `proc(var U32, Bool)`.
+	Proc(Vec<(bool, Self)>),
+	
+	/// a fixed number of values, each with its own type, like `(U32, Bool)`.
+	/// elements are accessed by their position, like `.0`.
+	Tuple(Vec<Self>),
+	
+	/// a user-defined struct, union, or alias, along with the types given for
+	/// its type parameters. the name may be a path into a module.
+	Decl(String, Vec<Self>),
+	
+	/// a type parameter of a generic procedure or type, like the `T` in
+	/// `proc swap_all[T] {a: Stack[T], b: Stack[T]}`.
+	Param(String),
+}
+
+impl Type {
+	/// Checks whether this is one of the fixed-size integer types, including
+	/// index types.
+	pub fn is_int(&self) -> bool {
+		self.bits().is_some() || matches!(self, Type::Index(_))
+	}
+	
+	/// Checks whether this is a signed integer type.
+	pub fn is_signed(&self) -> bool {
+		matches!(self, Type::I8 | Type::I16 | Type::I32 | Type::I64)
+	}
+	
+	/// The number of bits used by a fixed-size integer type.
+	pub fn bits(&self) -> Option<u32> {
+		match self {
+			Type::I8  | Type::U8  => Some(8),
+			Type::I16 | Type::U16 => Some(16),
+			Type::I32 | Type::U32 => Some(32),
+			Type::I64 | Type::U64 => Some(64),
+			_ => None,
+		}
+	}
+	
+	/// Checks whether this is any kind of integer type, including `Int`.
+	pub fn is_num(&self) -> bool {
+		self.is_int() || *self == Type::Int
+	}
+	
+	/// Checks whether a value of this type can be meaningfully converted to the
+	/// given type with `as`. Some casts may still fail at runtime if they would
+	/// lose information, like casting `2` to a `Bool`.
+	pub fn can_cast_to(&self, to: &Type) -> bool {
+		use Type::*;
+		let self_num = self.is_num() || *self == Bool || *self == Char;
+		let to_num = to.is_num() || *to == Bool || *to == Char;
+		
+		matches!((self, to),
+			(Char | String, Char | String)
+		) || (self_num && to_num && !matches!((self, to), (Bool, Char) | (Char, Bool)))
+		|| self == to
+	}
+}
+
+impl Type {
+	/// Matches this type against the type of an actual value, binding any type
+	/// parameters it contains along the way. Returns whether they fit.
+	/// `Infer` on either side fits anything, like the element type of `nil`.
+	pub fn unify(&self, concrete: &Type, bindings: &mut Vec<(String, Type)>) -> bool {
+		match (self, concrete) {
+			(Type::Infer, _) | (_, Type::Infer) => true,
+			
+			(Type::Param(name), _) => {
+				match bindings.iter().find(|(param, _)| param == name) {
+					Some((_, bound)) => bound.clone().unify(concrete, bindings),
+					None => {
+						bindings.push((name.clone(), concrete.clone()));
+						true
+					}
+				}
+			}
+			
+			(Type::Stack(a), Type::Stack(b)) => a.unify(b, bindings),
+			(Type::Fn(a_in, a_out), Type::Fn(b_in, b_out)) =>
+				a_in.unify(b_in, bindings) && a_out.unify(b_out, bindings),
+			(Type::Tuple(a), Type::Tuple(b)) =>
+				a.len() == b.len()
+				&& a.iter().zip(b).all(|(a, b)| a.unify(b, bindings)),
+			(Type::Proc(a), Type::Proc(b)) =>
+				a.len() == b.len()
+				&& a.iter().zip(b).all(|((a_var, a), (b_var, b))|
+					a_var == b_var && a.unify(b, bindings)
+				),
+			(Type::Decl(a, a_args), Type::Decl(b, b_args)) =>
+				a == b && a_args.len() == b_args.len()
+				&& a_args.iter().zip(b_args).all(|(a, b)| a.unify(b, bindings)),
+			
+			(a, b) => a == b,
+		}
+	}
+	
+	/// Replaces the type parameters in this type with the types they're bound
+	/// to. Unbound parameters are left as they are.
+	pub fn substitute(&self, bindings: &[(String, Type)]) -> Type {
+		match self {
+			Type::Param(name) => bindings.iter()
+				.find(|(param, _)| param == name)
+				.map_or_else(|| self.clone(), |(_, bound)| bound.clone()),
+			Type::Stack(elem) => Type::Stack(Box::new(elem.substitute(bindings))),
+			Type::Fn(a, b) => Type::Fn(
+				Box::new(a.substitute(bindings)),
+				Box::new(b.substitute(bindings)),
+			),
+			Type::Tuple(elems) => Type::Tuple(
+				elems.iter().map(|elem| elem.substitute(bindings)).collect(),
+			),
+			Type::Proc(params) => Type::Proc(
+				params.iter()
+					.map(|(var, typ)| (*var, typ.substitute(bindings)))
+					.collect(),
+			),
+			Type::Decl(name, args) => Type::Decl(
+				name.clone(),
+				args.iter().map(|arg| arg.substitute(bindings)).collect(),
+			),
+			_ => self.clone(),
+		}
+	}
+}
+
+// written the same way as in source code, so that aliases show up by name.
+impl fmt::Display for Type {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Type::Infer  => f.write_str("_"),
+			Type::Nil    => f.write_str("Nil"),
+			Type::Bool   => f.write_str("Bool"),
+			Type::I8     => f.write_str("I8"),
+			Type::I16    => f.write_str("I16"),
+			Type::I32    => f.write_str("I32"),
+			Type::I64    => f.write_str("I64"),
+			Type::U8     => f.write_str("U8"),
+			Type::U16    => f.write_str("U16"),
+			Type::U32    => f.write_str("U32"),
+			Type::U64    => f.write_str("U64"),
+			Type::Int    => f.write_str("Int"),
+			Type::Char   => f.write_str("Char"),
+			Type::String => f.write_str("Str"),
+			
+			// as a bijective numeral
+			Type::Index(n) => {
+				let mut n = *n;
+				let mut digits = Vec::new();
+				while n > 0 {
+					let digit = match n % 10 { 0 => 10, d => d };
+					digits.push(std::char::from_digit(digit, 11).unwrap().to_ascii_uppercase());
+					n = (n - digit) / 10;
+				}
+				f.write_str("0")?;
+				digits.iter().rev().try_for_each(|d| write!(f, "{}", d))
+			}
+			
+			Type::IndexOf(name) => write!(f, "0{}", name),
+			Type::Stack(elem) => write!(f, "Stack[{}]", elem),
+			// `->` is right associative
+			Type::Fn(arg, ret) if matches!(**arg, Type::Fn(..)) =>
+				write!(f, "({}) -> {}", arg, ret),
+			Type::Fn(arg, ret) => write!(f, "{} -> {}", arg, ret),
+			Type::Tuple(elems) => {
+				f.write_str("(")?;
+				for (i, elem) in elems.iter().enumerate() {
+					if i > 0 {
+						f.write_str(", ")?;
+					}
+					write!(f, "{}", elem)?;
+				}
+				if elems.len() == 1 {
+					f.write_str(",")?;
+				}
+				f.write_str(")")
+			}
+			Type::Proc(params) => {
+				f.write_str("proc(")?;
+				for (i, (var, typ)) in params.iter().enumerate() {
+					if i > 0 {
+						f.write_str(", ")?;
+					}
+					if *var {
+						f.write_str("var ")?;
+					}
+					write!(f, "{}", typ)?;
+				}
+				f.write_str(")")
+			}
+			Type::Decl(name, args) => {
+				f.write_str(name)?;
+				if !args.is_empty() {
+					f.write_str("[")?;
+					for (i, arg) in args.iter().enumerate() {
+						if i > 0 {
+							f.write_str(", ")?;
+						}
+						write!(f, "{}", arg)?;
+					}
+					f.write_str("]")?;
+				}
+				Ok(())
+			}
+			Type::Param(name) => f.write_str(name),
+		}
+	}
 }
 
 /// a generic type error.
@@ -59,13 +284,25 @@ impl error::Error for TypeErr {}
 
 impl Parser<'_> {
 	pub fn parse_type(&mut self) -> ParseResult<Type> {
+		// <atom> [ '->' <type> ]
+		let typ = self.parse_type_atom()?;
+		
+		if self.expect(Token::RightArrow).is_none() {
+			return Ok(typ);
+		}
+		
+		// right associative, so `02 -> 03 -> U32` is a 2x3 array
+		let ret = self.parse_type()?;
+		Ok(Type::Fn(Box::new(typ), Box::new(ret)))
+	}
+	
+	fn parse_type_atom(&mut self) -> ParseResult<Type> {
 		Ok(match self.peek().ok_or("a type")? {
 			Token::Underscore => Type::Infer,
 			
 			// named types with optional generics
-			Token::ConIdent => {
-				let name = self.slice().to_string();
-				self.next();
+			Token::ConIdent | Token::VarIdent => {
+				let name = self.parse_type_path()?;
 				
 				let mut type_params = Vec::new();
 				
@@ -89,42 +326,121 @@ impl Parser<'_> {
 					self.next();
 				}
 				
-				match name.as_str() {
+				let typ = match name.as_str() {
+					"Bool" => Type::Bool,
+					"I8"  => Type::I8,
+					"I16" => Type::I16,
+					"I32" => Type::I32,
+					"I64" => Type::I64,
+					"U8"  => Type::U8,
+					"U16" => Type::U16,
 					"U32" => Type::U32,
+					"U64" => Type::U64,
+					"Int" => Type::Int,
+					"Char" => Type::Char,
 					"Str" => Type::String,
-					_ => todo!()
+					"Stack" if type_params.len() == 1 =>
+						Type::Stack(Box::new(type_params.pop().unwrap())),
+					"Stack" => Err("one type parameter for `Stack`")?,
+					
+					// type parameters in scope shadow declared types
+					_ if self.type_params.contains(&name) => Type::Param(name),
+					// checked once all items are known
+					_ => return Ok(Type::Decl(name, type_params)),
+				};
+				
+				// only declared types and `Stack` take type parameters
+				if !type_params.is_empty() {
+					Err("no type parameters after a built-in type or type parameter")?;
 				}
+				typ
+			}
+			
+			// index types sized by a constant, like `0WORD_BITS`. the constant's
+			// name has to come right after the `0`.
+			Token::Number if self.slice() == "0" && {
+				let end = self.span().end;
+				let mut lookahead = self.clone();
+				lookahead.next();
+				lookahead.peek() == Some(&Token::ConIdent) && lookahead.span().start == end
+			} => {
+				self.next();
+				let name = self.slice().to_string();
+				self.next();
+				Type::IndexOf(name)
 			}
 			
-			Token::Number => {
-				todo!()
+			// index types
+			Token::Number => match self.parse_literal()? {
+				Literal::Num(n) => match u32::try_from(n) {
+					Ok(n) => Type::Index(n),
+					Err(_) => Err("an index type small enough to fit in 32 bits")?,
+				}
+				_ => unreachable!(),
 			}
-			/*
-			// tuples
+			// tuples. a single type in parentheses is just grouped, unless
+			// it has a trailing comma, like `(U32,)`.
 			Token::LParen => {
 				self.next();
-
+				
 				let mut types = Vec::new();
+				let mut trailing_comma = false;
 				loop {
 					match self.peek() {
 						Some(Token::RParen) => break,
 						Some(_) => {
 							types.push(self.parse_type()?);
-
+							
+							trailing_comma = match self.peek() {
+								Some(Token::Comma) => { self.next(); true }
+								Some(Token::RParen) => false,
+								_ => Err("`,` or `)` in tuple list")?,
+							};
+						}
+						None => Err("`,` or `)` in tuple list")?,
+					}
+				}
+				self.next();
+				
+				if types.len() == 1 && !trailing_comma {
+					types.pop().unwrap()
+				} else {
+					Type::Tuple(types)
+				}
+			}
+			
+			// procedures, like `proc(var U32, Bool)`. parameters that the
+			// procedure can modify are marked with `var`.
+			Token::Proc => {
+				self.next();
+				
+				self.expect(Token::LParen)
+					.ok_or("`(` for `proc` type")?;
+				
+				let mut params = Vec::new();
+				loop {
+					match self.peek() {
+						Some(Token::RParen) => break,
+						Some(_) => {
+							let var = self.expect(Token::Var).is_some();
+							let t = self.parse_type()?;
+							
+							params.push((var, t));
+							
 							match self.peek() {
 								Some(Token::Comma) => { self.next(); }
 								Some(Token::RParen) => {}
-								_ => Err("`,` or `)` in tuple list")?,
+								_ => Err("`,` or `)` in proc param list")?,
 							}
 						}
-						None => Err("`,` or `)` in tuple list")?,
+						None => Err("`)` or `,` in proc param list")?,
 					}
 				}
 				self.next();
-
-				Type::Tuple(types)
+				
+				Type::Proc(params)
 			}
-			
+			/*
 			// arrays
 			Token::LBracket => {
 				self.next();
@@ -176,36 +492,6 @@ impl Parser<'_> {
 				
 				Type::Fn(params, Box::new(ret))
 			}
-			
-			Token::Proc => {
-				self.next();
-				
-				self.expect(Token::LParen)
-					.ok_or("`(` for `proc` type")?;
-				
-				let mut params = Vec::new();
-				loop {
-					match self.peek() {
-						Some(Token::RParen) => break,
-						Some(_) => {
-							let var = self.expect(Token::Var).is_some();
-							let t = self.parse_type()?;
-							
-							params.push((var, t));
-							
-							match self.peek() {
-								Some(Token::Comma) => { self.next(); }
-								Some(Token::RParen) => {}
-								_ => Err("`,` or `)` in fn param list")?,
-							}
-						}
-						None => Err("`)` or `,` in proc param list")?,
-					}
-				}
-				self.next();
-				
-				Type::Proc(params)
-			}
 			*/
 			_ => Err("a valid type")?
 		})
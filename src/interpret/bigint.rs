@@ -0,0 +1,415 @@
+/*!
+Arbitrary-precision integers, used for the `Int` type. Nothing fancy, just the
+schoolbook algorithms, but arithmetic on them never overflows, which is what
+number-theoretic programs need to run backwards exactly.
+*/
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Neg};
+
+/// A signed integer of any size. The magnitude is stored as base 2^32 digits,
+/// least significant first, without any leading zeros. Zero has no digits and
+/// is never negative, so every number has exactly one representation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+	neg: bool,
+	mag: Vec<u32>,
+}
+
+impl BigInt {
+	fn from_parts(neg: bool, mut mag: Vec<u32>) -> BigInt {
+		trim(&mut mag);
+		let neg = neg && !mag.is_empty();
+		BigInt { neg, mag }
+	}
+	
+	/// Parses the digits of a number in the given radix, without a sign.
+	pub fn from_str_radix(digits: &str, radix: u32) -> Option<BigInt> {
+		let base = BigInt::from(radix as i128);
+		let mut n = BigInt::default();
+		for c in digits.chars() {
+			let digit = BigInt::from(c.to_digit(radix)? as i128);
+			n = &(&n * &base) + &digit;
+		}
+		Some(n)
+	}
+	
+	pub fn is_zero(&self) -> bool {
+		self.mag.is_empty()
+	}
+	
+	pub fn is_negative(&self) -> bool {
+		self.neg
+	}
+	
+	/// Converts to a machine integer, if the number is small enough.
+	pub fn to_i128(&self) -> Option<i128> {
+		if self.mag.len() > 4 {
+			return None;
+		}
+		
+		let mut n: u128 = 0;
+		for &digit in self.mag.iter().rev() {
+			n = n << 32 | digit as u128;
+		}
+		
+		if !self.neg {
+			i128::try_from(n).ok()
+		} else if n <= 1 << 127 {
+			Some((n as i128).wrapping_neg())
+		} else {
+			None
+		}
+	}
+	
+	pub fn pow(&self, mut exp: u32) -> BigInt {
+		let mut base = self.clone();
+		let mut acc = BigInt::from(1);
+		while exp > 0 {
+			if exp % 2 == 1 {
+				acc = &acc * &base;
+			}
+			base = &base * &base;
+			exp /= 2;
+		}
+		acc
+	}
+	
+	/// Divides, rounding toward zero. The remainder has the same sign as the
+	/// dividend. Gives `None` when dividing by zero.
+	pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+		if other.is_zero() {
+			return None;
+		}
+		
+		let (quot, rem) = div_rem_mag(&self.mag, &other.mag);
+		Some((
+			BigInt::from_parts(self.neg != other.neg, quot),
+			BigInt::from_parts(self.neg, rem),
+		))
+	}
+	
+	/// Divides so that the remainder is never negative, like `i32::rem_euclid`.
+	pub fn div_rem_euclid(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+		let (quot, rem) = self.div_rem(other)?;
+		let one = BigInt::from(1);
+		
+		Some(match (rem.neg, other.neg) {
+			(false, _) => (quot, rem),
+			(true, false) => (&quot - &one, &rem + other),
+			(true, true) => (&quot + &one, &rem - other),
+		})
+	}
+}
+
+impl From<i128> for BigInt {
+	fn from(n: i128) -> BigInt {
+		let mut abs = n.unsigned_abs();
+		let mut mag = Vec::new();
+		while abs != 0 {
+			mag.push(abs as u32);
+			abs >>= 32;
+		}
+		BigInt { neg: n < 0, mag }
+	}
+}
+
+impl Ord for BigInt {
+	fn cmp(&self, other: &BigInt) -> Ordering {
+		match (self.neg, other.neg) {
+			(false, true) => Ordering::Greater,
+			(true, false) => Ordering::Less,
+			(false, false) => cmp_mag(&self.mag, &other.mag),
+			(true, true) => cmp_mag(&other.mag, &self.mag),
+		}
+	}
+}
+
+impl PartialOrd for BigInt {
+	fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Neg for BigInt {
+	type Output = BigInt;
+	
+	fn neg(self) -> BigInt {
+		BigInt::from_parts(!self.neg, self.mag)
+	}
+}
+
+impl Add for &BigInt {
+	type Output = BigInt;
+	
+	fn add(self, other: &BigInt) -> BigInt {
+		if self.neg == other.neg {
+			return BigInt::from_parts(self.neg, add_mag(&self.mag, &other.mag));
+		}
+		
+		// signs differ, so subtract the smaller magnitude from the larger one
+		match cmp_mag(&self.mag, &other.mag) {
+			Ordering::Less =>
+				BigInt::from_parts(other.neg, sub_mag(&other.mag, &self.mag)),
+			_ =>
+				BigInt::from_parts(self.neg, sub_mag(&self.mag, &other.mag)),
+		}
+	}
+}
+
+impl Sub for &BigInt {
+	type Output = BigInt;
+	
+	fn sub(self, other: &BigInt) -> BigInt {
+		self + &-other.clone()
+	}
+}
+
+impl Mul for &BigInt {
+	type Output = BigInt;
+	
+	fn mul(self, other: &BigInt) -> BigInt {
+		let mut prod = vec![0u32; self.mag.len() + other.mag.len()];
+		
+		for (i, &l) in self.mag.iter().enumerate() {
+			let mut carry = 0u64;
+			for (j, &r) in other.mag.iter().enumerate() {
+				let n = prod[i + j] as u64 + l as u64 * r as u64 + carry;
+				prod[i + j] = n as u32;
+				carry = n >> 32;
+			}
+			prod[i + other.mag.len()] = carry as u32;
+		}
+		
+		BigInt::from_parts(self.neg != other.neg, prod)
+	}
+}
+
+impl fmt::Display for BigInt {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_zero() {
+			return f.write_str("0");
+		}
+		
+		// split into base 10^9 chunks, least significant first
+		let mut chunks = Vec::new();
+		let mut mag = self.mag.clone();
+		while !mag.is_empty() {
+			let (quot, rem) = div_rem_digit(&mag, 1_000_000_000);
+			chunks.push(rem);
+			mag = quot;
+		}
+		
+		if self.neg {
+			f.write_str("-")?;
+		}
+		write!(f, "{}", chunks.pop().unwrap())?;
+		for chunk in chunks.iter().rev() {
+			write!(f, "{:09}", chunk)?;
+		}
+		Ok(())
+	}
+}
+
+fn trim(mag: &mut Vec<u32>) {
+	while mag.last() == Some(&0) {
+		mag.pop();
+	}
+}
+
+fn cmp_mag(l: &[u32], r: &[u32]) -> Ordering {
+	l.len().cmp(&r.len())
+		.then_with(|| l.iter().rev().cmp(r.iter().rev()))
+}
+
+fn add_mag(l: &[u32], r: &[u32]) -> Vec<u32> {
+	let (long, short) = if l.len() >= r.len() { (l, r) } else { (r, l) };
+	let mut sum = Vec::with_capacity(long.len() + 1);
+	let mut carry = 0u64;
+	
+	for (i, &digit) in long.iter().enumerate() {
+		let n = digit as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+		sum.push(n as u32);
+		carry = n >> 32;
+	}
+	sum.push(carry as u32);
+	
+	trim(&mut sum);
+	sum
+}
+
+/// Subtracts magnitudes, where `l` must be at least as big as `r`.
+fn sub_mag(l: &[u32], r: &[u32]) -> Vec<u32> {
+	let mut diff = Vec::with_capacity(l.len());
+	let mut borrow = 0i64;
+	
+	for (i, &digit) in l.iter().enumerate() {
+		let mut n = digit as i64 - *r.get(i).unwrap_or(&0) as i64 - borrow;
+		borrow = 0;
+		if n < 0 {
+			n += 1 << 32;
+			borrow = 1;
+		}
+		diff.push(n as u32);
+	}
+	debug_assert_eq!(borrow, 0);
+	
+	trim(&mut diff);
+	diff
+}
+
+fn div_rem_digit(l: &[u32], r: u32) -> (Vec<u32>, u32) {
+	let mut quot = vec![0u32; l.len()];
+	let mut rem = 0u64;
+	
+	for (i, &digit) in l.iter().enumerate().rev() {
+		let n = rem << 32 | digit as u64;
+		quot[i] = (n / r as u64) as u32;
+		rem = n % r as u64;
+	}
+	
+	trim(&mut quot);
+	(quot, rem as u32)
+}
+
+fn div_rem_mag(l: &[u32], r: &[u32]) -> (Vec<u32>, Vec<u32>) {
+	if cmp_mag(l, r) == Ordering::Less {
+		return (Vec::new(), l.to_vec());
+	}
+	
+	if r.len() == 1 {
+		let (quot, rem) = div_rem_digit(l, r[0]);
+		return (quot, if rem == 0 { Vec::new() } else { vec![rem] });
+	}
+	
+	// binary long division, one bit at a time
+	let mut quot = vec![0u32; l.len()];
+	let mut rem: Vec<u32> = Vec::with_capacity(r.len() + 1);
+	
+	for bit in (0..l.len() * 32).rev() {
+		// rem = rem * 2 + next bit of l
+		let mut carry = l[bit / 32] >> (bit % 32) & 1;
+		for digit in rem.iter_mut() {
+			let next = *digit >> 31;
+			*digit = *digit << 1 | carry;
+			carry = next;
+		}
+		if carry != 0 {
+			rem.push(carry);
+		}
+		
+		if cmp_mag(&rem, r) != Ordering::Less {
+			rem = sub_mag(&rem, r);
+			quot[bit / 32] |= 1 << (bit % 32);
+		}
+	}
+	
+	trim(&mut quot);
+	(quot, rem)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	// a mix of signs, and magnitudes that take up one to four digits
+	const SAMPLES: [i128; 12] = [
+		0, 1, -1, 7, -7, u32::MAX as i128, -(u32::MAX as i128) - 1,
+		1 << 32, 1 << 63, -(1 << 70) + 12345, (1 << 90) - 1, -(1 << 95),
+	];
+	
+	fn big(s: &str) -> BigInt {
+		match s.strip_prefix('-') {
+			Some(digits) => -BigInt::from_str_radix(digits, 10).unwrap(),
+			None => BigInt::from_str_radix(s, 10).unwrap(),
+		}
+	}
+	
+	#[test]
+	fn add_sub() {
+		for l in SAMPLES {
+			for r in SAMPLES {
+				let (bl, br) = (BigInt::from(l), BigInt::from(r));
+				assert_eq!((&bl + &br).to_i128(), Some(l + r), "{} + {}", l, r);
+				assert_eq!((&bl - &br).to_i128(), Some(l - r), "{} - {}", l, r);
+			}
+		}
+	}
+	
+	#[test]
+	fn add_carries() {
+		let n = big("340282366920938463463374607431768211455"); // 2^128 - 1
+		assert_eq!((&n + &BigInt::from(1)).to_string(), "340282366920938463463374607431768211456");
+		assert_eq!((&BigInt::from(0) - &n).to_string(), "-340282366920938463463374607431768211455");
+	}
+	
+	#[test]
+	fn mul() {
+		for l in SAMPLES {
+			for r in SAMPLES {
+				if let Some(prod) = l.checked_mul(r) {
+					let bprod = &BigInt::from(l) * &BigInt::from(r);
+					assert_eq!(bprod.to_i128(), Some(prod), "{} * {}", l, r);
+				}
+			}
+		}
+		
+		let n = big("1000000000000000000000000000000");
+		assert_eq!((&n * &n).to_string(), format!("1{}", "0".repeat(60)));
+		assert_eq!((&n * &-n.clone()).to_string(), format!("-1{}", "0".repeat(60)));
+	}
+	
+	#[test]
+	fn div_rem() {
+		for l in SAMPLES {
+			for r in SAMPLES {
+				let result = BigInt::from(l).div_rem(&BigInt::from(r));
+				if r == 0 {
+					assert!(result.is_none());
+					continue;
+				}
+				let (quot, rem) = result.unwrap();
+				assert_eq!(quot.to_i128(), Some(l / r), "{} / {}", l, r);
+				assert_eq!(rem.to_i128(), Some(l % r), "{} % {}", l, r);
+			}
+		}
+	}
+	
+	// divisors of more than one digit take the long way
+	#[test]
+	fn div_rem_big() {
+		let n = big("100000000000000000000000000000000000000007");
+		let d = big("100000000000000000000");
+		let (quot, rem) = n.div_rem(&d).unwrap();
+		assert_eq!(quot.to_string(), "1000000000000000000000");
+		assert_eq!(rem.to_string(), "7");
+		assert_eq!(&(&quot * &d) + &rem, n);
+		
+		let (quot, rem) = (-n.clone()).div_rem_euclid(&d).unwrap();
+		assert_eq!(quot.to_string(), "-1000000000000000000001");
+		assert_eq!(rem.to_string(), "99999999999999999993");
+	}
+	
+	#[test]
+	fn cmp() {
+		let mut sorted = SAMPLES.to_vec();
+		sorted.sort();
+		let mut big_sorted: Vec<BigInt> = SAMPLES.iter().map(|&n| BigInt::from(n)).collect();
+		big_sorted.sort();
+		let big_sorted: Vec<i128> = big_sorted.iter().map(|n| n.to_i128().unwrap()).collect();
+		assert_eq!(big_sorted, sorted);
+		
+		assert!(big("-100000000000000000000000000000000000000000") < BigInt::from(i128::MIN));
+		assert!(big("100000000000000000000000000000000000000000") > BigInt::from(i128::MAX));
+	}
+	
+	// zero is never negative, however it's reached
+	#[test]
+	fn zero() {
+		let five = BigInt::from(5);
+		assert_eq!(&five - &five, BigInt::default());
+		assert_eq!(-BigInt::default(), BigInt::default());
+		assert!(!(&-five.clone() * &BigInt::default()).is_negative());
+	}
+}
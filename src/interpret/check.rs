@@ -0,0 +1,387 @@
+/*!
+Checks done on a whole program before it runs.
+
+For now this makes sure every type annotation names a type that exists, and
+gives generic types the right number of type parameters, and that functions
+stay pure. Procedure calls are checked against the declared types of the
+variables passed to them, which also decides the type arguments of generic
+procedures. Everything else about types is still checked while running.
+*/
+
+use std::ops::Range;
+
+use crate::ast::{BlockExpr, DeclKind, Expr, Literal, LValue, ProcDef, Stmt, Type};
+
+use super::{Context, EvalError, EvalResult};
+
+pub fn check_items(items: &Context) -> EvalResult<()> {
+	for decl in &items.types {
+		match &decl.kind {
+			DeclKind::Struct(fields) => for (_, typ) in fields {
+				check_type(items, typ)?;
+			}
+			DeclKind::Union(variants) => for typ in variants.iter().flat_map(|(_, typ)| typ) {
+				check_type(items, typ)?;
+			}
+			// aliases can't refer back to themselves, or expanding them would
+			// never finish
+			DeclKind::Alias(typ) => {
+				check_type(items, typ)?;
+				check_acyclic(items, typ, &mut vec![decl.name.clone()])?;
+			}
+		}
+	}
+	
+	for proc in &items.procs {
+		for param in &proc.params {
+			check_type(items, &param.typ)?;
+		}
+		if let ProcDef::User(code) = &proc.code {
+			let mut scope = Scope {
+				vars: proc.params.iter().map(|param| (param.name.as_str(), &param.typ)).collect(),
+				type_params: proc.type_params.iter()
+					.map(|param| (param.clone(), Type::Infer))
+					.collect(),
+				in_fn: false,
+			};
+			check_block(items, code, &mut scope)?;
+		}
+	}
+	
+	// functions and constants start out seeing no variables at all
+	let top = Scope { vars: Vec::new(), type_params: Vec::new(), in_fn: true };
+	
+	for func in &items.funcs {
+		for (_, typ) in &func.params {
+			check_type(items, typ)?;
+		}
+		check_type(items, &func.ret)?;
+		check_fn_body(items, &top, &func.params, &func.body)?;
+	}
+	
+	// constants are held to the same rules as function bodies
+	for (_, typ, expr) in &items.pending_consts {
+		check_type(items, typ)?;
+		check_expr(items, expr, &top)?;
+	}
+	
+	Ok(())
+}
+
+// the variables that are visible at some point in the program, and their
+// declared types. function bodies only see their parameters and bindings, and
+// whatever a function literal captures from around it.
+struct Scope<'a> {
+	vars: Vec<(&'a str, &'a Type)>,
+	// the type parameters of the procedure being checked, which could be bound
+	// to anything, so they're left to be inferred.
+	type_params: Vec<(String, Type)>,
+	in_fn: bool,
+}
+
+impl Scope<'_> {
+	fn contains(&self, name: &str) -> bool {
+		self.vars.iter().any(|(var, _)| *var == name)
+	}
+	
+	// the type an argument is known to have without running anything, if it's
+	// a variable with a declared type or a literal that can only be one type.
+	fn type_of(&self, items: &Context, expr: &Expr) -> Option<Type> {
+		let typ = match expr {
+			Expr::LVal(lval) if lval.ops.is_empty() => self.vars.iter().rev()
+				.find(|(var, _)| *var == lval.id)
+				.map(|(_, typ)| (*typ).clone())?,
+			Expr::Lit(lit @ (Literal::Bool(_) | Literal::Char(_) | Literal::String(_))) =>
+				lit.get_type()?,
+			_ => return None,
+		};
+		// constants in types aren't known until they've been evaluated
+		items.expand(&typ.substitute(&self.type_params)).ok()
+			.filter(|typ| *typ != Type::Infer)
+	}
+}
+
+fn check_type(items: &Context, typ: &Type) -> EvalResult<()> {
+	match typ {
+		Type::Decl(name, args) => {
+			let decl = items.find_type(name)?;
+			if decl.params.len() != args.len() {
+				return Err(EvalError::TypeArity {
+					name: name.clone(),
+					expected: decl.params.len(),
+					got: args.len(),
+				});
+			}
+			for arg in args {
+				check_type(items, arg)?;
+			}
+		}
+		// constants aren't evaluated yet, so only their name can be checked
+		Type::IndexOf(name) if items.find_const(name).is_none()
+		&& !items.pending_consts.iter().any(|(constant, ..)| constant == name) =>
+			return Err(EvalError::UnknownIdent(name.clone())),
+		Type::Stack(elem) => check_type(items, elem)?,
+		Type::Fn(arg, ret) => {
+			check_type(items, arg)?;
+			check_type(items, ret)?;
+		}
+		Type::Tuple(elems) => for elem in elems {
+			check_type(items, elem)?;
+		}
+		Type::Proc(params) => for (_, typ) in params {
+			check_type(items, typ)?;
+		}
+		_ => {}
+	}
+	Ok(())
+}
+
+fn check_acyclic(items: &Context, typ: &Type, seen: &mut Vec<String>) -> EvalResult<()> {
+	match typ {
+		Type::Decl(name, args) => {
+			for arg in args {
+				check_acyclic(items, arg, seen)?;
+			}
+			
+			let decl = items.find_type(name)?;
+			if let DeclKind::Alias(target) = &decl.kind {
+				if seen.contains(&decl.name) {
+					return Err(EvalError::CyclicAlias(decl.name.clone()));
+				}
+				seen.push(decl.name.clone());
+				check_acyclic(items, target, seen)?;
+				seen.pop();
+			}
+		}
+		Type::Stack(elem) => check_acyclic(items, elem, seen)?,
+		Type::Fn(arg, ret) => {
+			check_acyclic(items, arg, seen)?;
+			check_acyclic(items, ret, seen)?;
+		}
+		Type::Tuple(elems) => for elem in elems {
+			check_acyclic(items, elem, seen)?;
+		}
+		Type::Proc(params) => for (_, typ) in params {
+			check_acyclic(items, typ, seen)?;
+		}
+		_ => {}
+	}
+	Ok(())
+}
+
+fn check_block<'a>(items: &Context, block: &'a [Stmt], scope: &mut Scope<'a>) -> EvalResult<()> {
+	for stmt in block {
+		check_stmt(items, stmt, scope)?;
+	}
+	Ok(())
+}
+
+fn check_stmt<'a>(items: &Context, stmt: &'a Stmt, scope: &mut Scope<'a>) -> EvalResult<()> {
+	match stmt {
+		Stmt::Skip => {}
+		
+		Stmt::Not(lval) | Stmt::Neg(lval) => check_lval(items, lval, scope)?,
+		
+		Stmt::RotLeft(lval, expr) | Stmt::RotRight(lval, expr)
+		| Stmt::Xor(lval, expr) | Stmt::Add(lval, expr) | Stmt::Sub(lval, expr)
+		| Stmt::Mul(lval, expr) | Stmt::Div(lval, expr) => {
+			check_lval(items, lval, scope)?;
+			check_expr(items, expr, scope)?;
+		}
+		
+		Stmt::Swap(left, right) => {
+			check_lval(items, left, scope)?;
+			check_lval(items, right, scope)?;
+		}
+		Stmt::CSwap(test, left, right) => {
+			check_expr(items, test, scope)?;
+			check_lval(items, left, scope)?;
+			check_lval(items, right, scope)?;
+		}
+		
+		Stmt::Destructure(_, lvals, stmt) => {
+			for lval in lvals {
+				check_lval(items, lval, scope)?;
+			}
+			check_stmt(items, stmt, scope)?;
+		}
+		
+		// variables holding a procedure are only known when they're called
+		Stmt::Do(name, args, span) | Stmt::Undo(name, args, span) => {
+			if !scope.contains(name) {
+				let proc = items.find_proc(name)
+					.ok_or_else(|| EvalError::OutOfScope { name: name.clone(), span: span.clone() })?;
+				if proc.params.len() != args.len() {
+					return Err(EvalError::ArgCount {
+						name: name.clone(),
+						expected: proc.params.len(),
+						got: args.len(),
+						span: span.clone(),
+					});
+				}
+				
+				// type parameters have to be bound the same way everywhere
+				let mut type_args = Vec::new();
+				for (param, arg) in proc.params.iter().zip(args) {
+					let (Some(got), Ok(expected)) = (scope.type_of(items, arg), items.expand(&param.typ))
+					else { continue };
+					if !expected.unify(&got, &mut type_args) {
+						return Err(EvalError::TypeMismatch {
+							expected: expected.substitute(&type_args),
+							got,
+						});
+					}
+				}
+			}
+			for arg in args {
+				check_expr(items, arg, scope)?;
+			}
+		}
+		
+		Stmt::Var(name, typ, init, block, dest) => {
+			check_type(items, typ)?;
+			check_expr(items, init, scope)?;
+			scope.vars.push((name, typ));
+			check_block(items, block, scope)?;
+			scope.vars.pop();
+			check_expr(items, dest, scope)?;
+		}
+		Stmt::If(test, block, else_block, assert)
+		| Stmt::From(test, block, else_block, assert) => {
+			check_expr(items, test, scope)?;
+			check_block(items, block, scope)?;
+			check_block(items, else_block, scope)?;
+			check_expr(items, assert, scope)?;
+		}
+		Stmt::FromVar(name, init, block, loop_block, end) => {
+			check_expr(items, init, scope)?;
+			scope.vars.push((name, &Type::Infer));
+			check_block(items, block, scope)?;
+			check_block(items, loop_block, scope)?;
+			check_expr(items, end, scope)?;
+			scope.vars.pop();
+		}
+	}
+	Ok(())
+}
+
+fn check_fn_body<'a>(
+	items: &Context,
+	outer: &Scope<'a>,
+	params: &'a [(String, Type)],
+	body: &'a BlockExpr,
+) -> EvalResult<()> {
+	let mut scope = Scope {
+		vars: outer.vars.iter().copied()
+			.chain(params.iter().map(|(name, typ)| (name.as_str(), typ)))
+			.collect(),
+		type_params: outer.type_params.clone(),
+		in_fn: true,
+	};
+	check_block_expr(items, body, &mut scope)
+}
+
+// bindings can't change, so a `let` can't rebind anything already in scope
+fn check_block_expr<'a>(items: &Context, block: &'a BlockExpr, scope: &mut Scope<'a>) -> EvalResult<()> {
+	match block {
+		BlockExpr::Inline(expr) => check_expr(items, expr, scope),
+		BlockExpr::If(test, then, otherwise) => {
+			check_expr(items, test, scope)?;
+			check_block_expr(items, then, scope)?;
+			check_block_expr(items, otherwise, scope)
+		}
+		BlockExpr::Let(binding, typ, init, rest) => {
+			check_type(items, typ)?;
+			check_expr(items, init, scope)?;
+			
+			let names = binding.names();
+			for (i, name) in names.iter().enumerate() {
+				if scope.contains(name) || names[..i].contains(name) {
+					return Err(EvalError::Rebinding(name.to_string()));
+				}
+			}
+			
+			let len = scope.vars.len();
+			scope.vars.extend(names.into_iter().map(|name| (name, &Type::Infer)));
+			let result = check_block_expr(items, rest, scope);
+			scope.vars.truncate(len);
+			result
+		}
+	}
+}
+
+// inside a function, a name that isn't a variable in scope can only be
+// another function. procedures, intrinsics included, could have effects.
+fn check_name(items: &Context, name: &str, span: &Range<usize>, scope: &Scope) -> EvalResult<()> {
+	if !scope.in_fn || scope.contains(name) || items.find_fn(name).is_some() {
+		Ok(())
+	} else if items.find_proc(name).is_some() {
+		Err(EvalError::ImpureCall { name: name.to_string(), span: span.clone() })
+	} else {
+		Err(EvalError::OutOfScope { name: name.to_string(), span: span.clone() })
+	}
+}
+
+fn check_lval(items: &Context, lval: &LValue, scope: &Scope) -> EvalResult<()> {
+	check_name(items, &lval.id, &lval.span, scope)?;
+	for arg in lval.ops.iter().flat_map(|op| op.args.iter().flatten()) {
+		check_expr(items, arg, scope)?;
+	}
+	Ok(())
+}
+
+fn check_expr<'a>(items: &Context, expr: &'a Expr, scope: &Scope<'a>) -> EvalResult<()> {
+	match expr {
+		Expr::Lit(Literal::Array(elems) | Literal::Tuple(elems)) => for elem in elems {
+			check_expr(items, elem, scope)?;
+		}
+		Expr::Lit(Literal::Struct(_, fields)) => for (_, field) in fields {
+			check_expr(items, field, scope)?;
+		}
+		Expr::Lit(Literal::Variant(_, Some(value))) => check_expr(items, value, scope)?,
+		Expr::Lit(Literal::Fn(func)) => {
+			for (_, typ) in &func.params {
+				check_type(items, typ)?;
+			}
+			check_type(items, &func.ret)?;
+			check_fn_body(items, scope, &func.params, &func.body)?;
+		}
+		Expr::Lit(_) => {}
+		Expr::LVal(lval) => check_lval(items, lval, scope)?,
+		Expr::Cast(expr, typ) => {
+			check_expr(items, expr, scope)?;
+			check_type(items, typ)?;
+		}
+		Expr::Neg(expr) | Expr::Not(expr) => check_expr(items, expr, scope)?,
+		Expr::App(name, args, span) => {
+			check_name(items, name, span, scope)?;
+			
+			// a variable holding a function is only known when it runs
+			match items.find_fn(name) {
+				Some(func) if !scope.contains(name)
+				&& func.params.len() != args.len() =>
+					return Err(EvalError::ArgCount {
+						name: name.clone(),
+						expected: func.params.len(),
+						got: args.len(),
+						span: span.clone(),
+					}),
+				_ => {}
+			}
+			for arg in args {
+				check_expr(items, arg, scope)?;
+			}
+		}
+		Expr::BinOp(left, _, right) => {
+			check_expr(items, left, scope)?;
+			check_expr(items, right, scope)?;
+		}
+		Expr::Chain(first, rest) => {
+			check_expr(items, first, scope)?;
+			for (_, expr) in rest {
+				check_expr(items, expr, scope)?;
+			}
+		}
+	}
+	Ok(())
+}
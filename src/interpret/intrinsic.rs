@@ -3,8 +3,8 @@
 use std::io::prelude::*;
 use std::sync::Mutex;
 
-use super::{EvalResult, EvalError, Value, io::{RevStdout, RevStdin}};
-use crate::ast::{Type, ProcDef};
+use super::{EvalResult, EvalError, Str, Value, io::{RevStdout, RevStdin}};
+use crate::ast::{Type, Param, ProcDef, Procedure};
 
 lazy_static::lazy_static! {
 	static ref STDOUT: Mutex<RevStdout> = Mutex::new(RevStdout::new());
@@ -17,8 +17,8 @@ pub fn print(args: &mut [Value]) -> EvalResult<()> {
 	match args {
 		[Value::String(string), Value::U32(bytes)] => {
 			let mut stdout = STDOUT.lock().unwrap();
-			let bytes_read = stdout.write(string.as_bytes()).unwrap();
-			*string = string.split_off(bytes_read);
+			let bytes_read = stdout.write(string.as_str().as_bytes()).unwrap();
+			*string = Str::from(&string.as_str()[bytes_read..]);
 			*bytes += bytes_read as u32;
 			Ok(())
 		}
@@ -45,7 +45,7 @@ pub fn unprint(args: &mut [Value]) -> EvalResult<()> {
 			let mut stdout = STDOUT.lock().unwrap();
 			let s = String::from_utf8(stdout.unwrite(*bytes as usize)).unwrap();
 			*bytes -= s.len() as u32;
-			*string = s + &string;
+			*string = Str::from(s + string.as_str());
 			Ok(())
 		}
 		[Value::String(_), val] =>
@@ -62,12 +62,222 @@ pub fn unprint(args: &mut [Value]) -> EvalResult<()> {
 	}
 }
 
+// Arguments: value:T, stack:Stack[T]
+// Action: moves value onto the top of stack, and leaves value as zero.
+pub fn push(args: &mut [Value]) -> EvalResult<()> {
+	match args {
+		[value, Value::Stack(stack, elem)] => {
+			// `nil` doesn't know its element type until the first push
+			if *elem == Type::Infer {
+				*elem = value.get_type();
+			} else if value.get_type() != *elem {
+				return Err(EvalError::TypeMismatch {
+					expected: elem.clone(),
+					got: value.get_type(),
+				});
+			}
+			
+			let zero = value.zeroed();
+			stack.push(std::mem::replace(value, zero));
+			Ok(())
+		}
+		[_, val] =>
+			Err(EvalError::TypeMismatch {
+				expected: Type::Stack(Box::new(Type::Infer)),
+				got: val.get_type(),
+			}),
+		_ => panic!("wrong number of parameters: expected 2, got {}", args.len()),
+	}
+}
+
+// Arguments: value:T, stack:Stack[T]
+// Action: moves the top of stack into value, which must be zero beforehand.
+pub fn pop(args: &mut [Value]) -> EvalResult<()> {
+	match args {
+		[value, Value::Stack(stack, elem)] => {
+			let top = stack.last().ok_or(EvalError::EmptyStack)?;
+			
+			if value.get_type() != *elem {
+				return Err(EvalError::TypeMismatch {
+					expected: elem.clone(),
+					got: value.get_type(),
+				});
+			}
+			// anything else would be overwritten and lost
+			if *value != value.zeroed() {
+				return Err(EvalError::IrreversibleState);
+			}
+			
+			*value = top.clone();
+			stack.pop();
+			Ok(())
+		}
+		[_, val] =>
+			Err(EvalError::TypeMismatch {
+				expected: Type::Stack(Box::new(Type::Infer)),
+				got: val.get_type(),
+			}),
+		_ => panic!("wrong number of parameters: expected 2, got {}", args.len()),
+	}
+}
+
+// Arguments: value:T
+// Action: reads a byte from stdin into value, which must be zero beforehand.
+//         At the end of input, value is left as zero.
+pub fn get_char(args: &mut [Value]) -> EvalResult<()> {
+	match args {
+		[value] => {
+			if *value != value.zeroed() {
+				return Err(EvalError::IrreversibleState);
+			}
+			
+			let mut byte = [0];
+			let bytes_read = STDIN.lock().unwrap().read(&mut byte).unwrap();
+			if bytes_read == 0 {
+				return Ok(());
+			}
+			
+			*value = match value {
+				Value::Char(_) => Value::Char(byte[0] as char),
+				_ => Value::exact_int(byte[0] as i128, &value.get_type())
+					.ok_or(EvalError::TypeMismatch {
+						expected: Type::U8,
+						got: value.get_type(),
+					})?,
+			};
+			Ok(())
+		}
+		_ => panic!("wrong number of parameters: expected 1, got {}", args.len()),
+	}
+}
+
+// Arguments: value:T
+// Action: moves the byte in value back to stdin, and leaves value as zero. A
+//         zero value has nothing to give back, as if the input had ended.
+pub fn unget_char(args: &mut [Value]) -> EvalResult<()> {
+	match args {
+		[value] => {
+			let byte = match value {
+				Value::Char(c) => u8::try_from(*c as u32).ok(),
+				_ => value.as_int().and_then(|n| u8::try_from(n).ok()),
+			};
+			let byte = byte.ok_or(EvalError::TypeMismatch {
+				expected: Type::U8,
+				got: value.get_type(),
+			})?;
+			
+			if byte != 0 {
+				STDIN.lock().unwrap().unread(&mut [byte], 1);
+			}
+			*value = value.zeroed();
+			Ok(())
+		}
+		_ => panic!("wrong number of parameters: expected 1, got {}", args.len()),
+	}
+}
+
 
 pub static PRINT_PROCDEF: ProcDef = ProcDef::Internal {
 	fore: print,
 	back: unprint,
 };
 
+pub static SHOW_PROCDEF: ProcDef = ProcDef::Internal {
+	fore: show,
+	back: unshow,
+};
+
+pub static GET_CHAR_PROCDEF: ProcDef = ProcDef::Internal {
+	fore: get_char,
+	back: unget_char,
+};
+
+pub static PUSH_PROCDEF: ProcDef = ProcDef::Internal {
+	fore: push,
+	back: pop,
+};
+
+pub static POP_PROCDEF: ProcDef = ProcDef::Internal {
+	fore: pop,
+	back: push,
+};
+
+// the element and stack types are checked by `push` and `pop` themselves.
+fn stack_params() -> Vec<Param> {
+	vec![
+		Param {
+			name: "value".into(),
+			constant: false,
+			typ: Type::Infer,
+		},
+		Param {
+			name: "stack".into(),
+			constant: false,
+			typ: Type::Infer,
+		},
+	]
+}
+
+/// Returns all intrinsic procedures that are available to every program.
+pub fn procs() -> Vec<Procedure> {
+	vec![
+		Procedure {
+			name: "print".into(),
+			type_params: Vec::new(),
+			params: vec![
+				Param {
+					name: "msg".into(),
+					constant: false,
+					typ: Type::String,
+				},
+				Param {
+					name: "bytes_read".into(),
+					constant: false,
+					typ: Type::U32,
+				},
+			],
+			code: PRINT_PROCDEF.clone(),
+		},
+		Procedure {
+			name: "show".into(),
+			type_params: Vec::new(),
+			params: vec![
+				Param {
+					name: "string".into(),
+					constant: true,
+					typ: Type::String,
+				},
+			],
+			code: SHOW_PROCDEF.clone(),
+		},
+		Procedure {
+			name: "get_char".into(),
+			type_params: Vec::new(),
+			params: vec![
+				// any integer or character, checked by `get_char` itself
+				Param {
+					name: "value".into(),
+					constant: false,
+					typ: Type::Infer,
+				},
+			],
+			code: GET_CHAR_PROCDEF.clone(),
+		},
+		Procedure {
+			name: "push".into(),
+			type_params: Vec::new(),
+			params: stack_params(),
+			code: PUSH_PROCDEF.clone(),
+		},
+		Procedure {
+			name: "pop".into(),
+			type_params: Vec::new(),
+			params: stack_params(),
+			code: POP_PROCDEF.clone(),
+		},
+	]
+}
+
 
 
 
@@ -78,7 +288,7 @@ pub fn show(args: &mut [Value]) -> EvalResult<()> {
 	let mut stdout = STDOUT.lock().unwrap();
 	
 	if let Value::String(string) = &args[0] {
-		stdout.write(string.as_bytes()).unwrap();
+		stdout.write_all(string.as_str().as_bytes()).unwrap();
 		Ok(())
 	} else {
 		Err(EvalError::TypeMismatch {
@@ -94,8 +304,8 @@ pub fn unshow(args: &mut [Value]) -> EvalResult<()> {
 	let mut stdout = STDOUT.lock().unwrap();
 	
 	if let Value::String(string) = &args[0] {
-		let extracted_data = stdout.unwrite(string.len());
-		assert_eq!(string.as_bytes(), extracted_data.as_slice());
+		let extracted_data = stdout.unwrite(string.as_str().len());
+		assert_eq!(string.as_str().as_bytes(), extracted_data.as_slice());
 		Ok(())
 	} else {
 		Err(EvalError::TypeMismatch {
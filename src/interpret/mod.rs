@@ -1,12 +1,18 @@
 //use std::io::prelude::*;
 use std::fmt;
+use std::ops::Range;
 
 use crate::ast::{self, Item, Module, Type, Procedure, Param, ProcDef};
 
 pub use self::value::Value;
+pub use self::bigint::BigInt;
+pub use self::string::Str;
 pub use self::stack::{Stack, StackFrame, Context};
 
+mod bigint;
+mod check;
 mod io;
+mod string;
 mod value;
 pub mod intrinsic;
 mod stack;
@@ -22,6 +28,71 @@ pub enum EvalError {
 	},
 	UnknownIdent(String),
 	IrreversibleState,
+	LossyCast {
+		value: Box<Value>,
+		to: Type,
+	},
+	OutOfRange {
+		value: i128,
+		typ: Type,
+	},
+	DivideByZero,
+	OutOfBounds {
+		index: i128,
+		len: usize,
+		span: Range<usize>,
+	},
+	EmptyStack,
+	UnknownType(String),
+	TypeArity {
+		name: String,
+		expected: usize,
+		got: usize,
+	},
+	NoField {
+		typ: Type,
+		field: String,
+	},
+	NoDeref {
+		typ: Type,
+		deref: String,
+		span: Range<usize>,
+	},
+	MissingField {
+		typ: Type,
+		field: String,
+	},
+	CyclicAlias(String),
+	CyclicConst(String),
+	Rebinding(String),
+	OutOfScope {
+		name: String,
+		span: Range<usize>,
+	},
+	ImpureCall {
+		name: String,
+		span: Range<usize>,
+	},
+	ArgCount {
+		name: String,
+		expected: usize,
+		got: usize,
+		span: Range<usize>,
+	},
+}
+
+impl EvalError {
+	/// The location in the source code where the error happened, if known.
+	pub fn span(&self) -> Option<Range<usize>> {
+		match self {
+			EvalError::OutOfBounds { span, .. }
+			| EvalError::OutOfScope { span, .. }
+			| EvalError::ImpureCall { span, .. }
+			| EvalError::ArgCount { span, .. }
+			| EvalError::NoDeref { span, .. } => Some(span.clone()),
+			_ => None,
+		}
+	}
 }
 
 impl fmt::Display for EvalError {
@@ -30,44 +101,79 @@ impl fmt::Display for EvalError {
 			EvalError::UnknownIdent(id) =>
 				write!(f, "name {:?} is not defined", id),
 			EvalError::TypeMismatch { expected, got } =>
-				write!(f, "expected {:?}, got {:?}", expected, got),
+				write!(f, "expected {}, got {}", expected, got),
 			EvalError::IrreversibleState =>
 				f.write_str("hit an irreversible state"),
+			EvalError::LossyCast { value, to } =>
+				write!(f, "can't cast {} to {} without losing information", value, to),
+			EvalError::OutOfRange { value, typ } =>
+				write!(f, "{} is out of range for {}", value, typ),
+			EvalError::DivideByZero =>
+				f.write_str("tried to divide by zero"),
+			EvalError::OutOfBounds { index, len, .. } =>
+				write!(f, "index {} is out of bounds for length {}", index, len),
+			EvalError::EmptyStack =>
+				f.write_str("tried to take from an empty stack"),
+			EvalError::UnknownType(name) =>
+				write!(f, "type {:?} is not defined", name),
+			EvalError::TypeArity { name, expected, got } =>
+				write!(f, "expected {} type parameters for {:?}, got {}", expected, name, got),
+			EvalError::NoField { typ, field } =>
+				write!(f, "{} has no field {:?}", typ, field),
+			EvalError::NoDeref { typ, deref, .. } =>
+				write!(f, "{} has no {}", typ, deref),
+			EvalError::MissingField { typ, field } =>
+				write!(f, "missing field {:?} for {}", field, typ),
+			EvalError::CyclicAlias(name) =>
+				write!(f, "alias {:?} refers to itself", name),
+			EvalError::CyclicConst(name) =>
+				write!(f, "constant {:?} depends on itself", name),
+			EvalError::Rebinding(name) =>
+				write!(f, "{:?} is already bound, and bindings can't change", name),
+			EvalError::OutOfScope { name, .. } =>
+				write!(f, "{:?} isn't in scope here", name),
+			EvalError::ImpureCall { name, .. } =>
+				write!(f, "functions can't use procedure {:?}, since it could have side effects", name),
+			EvalError::ArgCount { name, expected, got, .. } =>
+				write!(f, "expected {} arguments for {:?}, got {}", expected, name, got),
 		}
 	}
 }
-/*
-// Creates root module, loads intrinsics, finds `main`, and executes it.
-pub fn interpret_file(items: Vec<ast::Item>) {
-	// create root module
-	let mut root = Module::new("root".into(), items);
-		
-	root.items.push(
-		Item::Proc(Procedure {
-			name: "show".to_string(),
-			params: vec![Param {
-				name: "string".to_string(),
-				constant: false,
-				typ: Type::String,
-			}],
-			code: ProcDef::Internal {
-				fore: intrinsic::show,
-				back: intrinsic::unshow
-			},
-		})
-	);
+
+/// Creates the root context, loads intrinsics, and checks the file's items
+/// before anything runs.
+pub fn load_file(items: Vec<Item>) -> EvalResult<Context> {
+	let mut root = Context::new();
 	
-	// find main procedure
-	let main = root.items.iter()
-		.find(|item| matches!(item, Item::Proc(pr) if pr.name == "main"));
+	for pr in intrinsic::procs() {
+		root.insert(Item::Proc(pr));
+	}
+	for item in items {
+		root.insert(item);
+	}
 	
-	// TODO set up stack
+	check::check_items(&root)?;
+	Ok(root)
+}
+
+/// Evaluates constants, finds `main`, and executes it.
+pub fn interpret_file(mut root: Context) -> EvalResult<()> {
+	// constants are only evaluated once they've passed the checks, since
+	// they can call any function
+	root.eval_consts()?;
+	
+	// find main procedure
+	let main = root.procs.iter()
+		.find(|pr| pr.name == "main")
+		.cloned();
 	
 	// run main procedure, if any
-	if let Some(Item::Proc(pr)) = main {
-		pr.call(Vec::new(), &root).unwrap();
-	} else {
-		eprintln!("No main procedure found.");
+	match main {
+		Some(pr) => {
+			pr.call(root, Vec::new())?;
+		}
+		None => eprintln!("rever: No main procedure found."),
 	}
+	
+	Ok(())
 }
-*/
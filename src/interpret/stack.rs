@@ -1,6 +1,9 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
 use super::*;
 
-use crate::ast::{LValue, Deref, Module, Function, Procedure};
+use crate::ast::{DeclKind, Expr, LValue, Deref, Module, Function, Procedure, TypeDecl};
 
 /// Contains the various items that can be used within the evoking item.
 #[derive(Debug, Clone)]
@@ -8,6 +11,14 @@ pub struct Context {
 	pub funcs: Vec<Function>,
 	pub procs: Vec<Procedure>,
 	pub mods: Vec<Module>,
+	/// types declared here or in any module below, by their full path
+	pub types: Vec<TypeDecl>,
+	/// names brought in with `use`, and the paths they stand for
+	pub imports: Vec<(String, String)>,
+	/// values of constants, which are evaluated once by `eval_consts`
+	pub consts: Vec<(String, Value)>,
+	/// constants that were inserted but haven't been evaluated yet
+	pub pending_consts: Vec<(String, Type, Expr)>,
 }
 
 /// Stores values of parameters and local variables during a function or
@@ -17,6 +28,8 @@ pub struct StackFrame {
 	names: Vec<String>,
 	pub(crate) values: Vec<Value>,
 	pub(crate) items: Context,
+	/// what the type parameters of a generic procedure are bound to
+	pub(crate) type_args: Vec<(String, Type)>,
 }
 
 pub type Stack = Vec<StackFrame>;
@@ -27,6 +40,10 @@ impl Context {
 			funcs: Vec::new(),
 			procs: Vec::new(),
 			mods: Vec::new(),
+			types: Vec::new(),
+			imports: Vec::new(),
+			consts: Vec::new(),
+			pending_consts: Vec::new(),
 		}
 	}
 	
@@ -38,20 +55,208 @@ impl Context {
 		ctx
 	}
 	
+	/// Adds an item to the context. Constants are only evaluated later by
+	/// `eval_consts`, once everything they could use is there.
 	pub fn insert(&mut self, item: Item) {
 		match item {
+			Item::Const(name, typ, expr) => self.pending_consts.push((name, typ, expr)),
 			Item::Proc(p) => self.procs.push(p),
 			Item::Fn(f) => self.funcs.push(f),
-			_ => todo!()
+			Item::Type(t) => self.types.push(t),
+			Item::Use(path, name) => {
+				let name = Item::Use(path.clone(), name).get_name().to_string();
+				self.imports.push((name, path));
+			}
+			Item::Mod(m) => {
+				self.insert_types(&m, "");
+				self.mods.push(m);
+			}
+		}
+	}
+	
+	/// Evaluates the pending constants. A constant can use ones declared
+	/// after it, so a constant that needs one that isn't known yet waits for
+	/// it. When none of the waiting ones can go ahead, they refer to each
+	/// other.
+	pub fn eval_consts(&mut self) -> EvalResult<()> {
+		let mut pending = std::mem::take(&mut self.pending_consts);
+		while !pending.is_empty() {
+			let len = pending.len();
+			let mut i = 0;
+			while i < pending.len() {
+				let (_, typ, expr) = &pending[i];
+				match self.eval_const(typ, expr) {
+					Ok(value) => {
+						let (name, ..) = pending.remove(i);
+						self.consts.push((name, value));
+					}
+					Err(EvalError::UnknownIdent(dep))
+					if pending.iter().any(|(name, ..)| *name == dep) => i += 1,
+					Err(e) => return Err(e),
+				}
+			}
+			
+			if pending.len() == len {
+				return Err(EvalError::CyclicConst(pending.swap_remove(0).0));
+			}
+		}
+		Ok(())
+	}
+	
+	fn eval_const(&self, typ: &Type, expr: &Expr) -> EvalResult<Value> {
+		let typ = self.expand(typ)?;
+		let value = expr.eval_as(&StackFrame::new(self.clone(), Vec::new()), &typ)?;
+		if typ != Type::Infer && value.get_type() != typ {
+			return Err(EvalError::TypeMismatch {
+				expected: typ,
+				got: value.get_type(),
+			});
+		}
+		Ok(value)
+	}
+	
+	pub fn find_fn(&self, name: &str) -> Option<&Function> {
+		self.funcs.iter().find(|func| func.name == name)
+	}
+	
+	pub fn find_proc(&self, name: &str) -> Option<&Procedure> {
+		self.procs.iter().find(|proc| proc.name == name)
+	}
+	
+	pub fn find_const(&self, name: &str) -> Option<&Value> {
+		self.consts.iter().rev()
+			.find(|(constant, _)| constant == name)
+			.map(|(_, value)| value)
+	}
+	
+	// types in modules are known by their full path, like `io::Byte`. inside
+	// the module they're declared in, they can be used by just their name.
+	fn insert_types(&mut self, module: &Module, prefix: &str) {
+		let prefix = format!("{}{}::", prefix, module.name);
+		let siblings: Vec<&str> = module.items.iter()
+			.filter(|item| matches!(item, Item::Type(_)))
+			.map(Item::get_name)
+			.collect();
+		let qualify = |typ: &Type| qualify_type(typ, &prefix, &siblings);
+		
+		for item in &module.items {
+			match item {
+				Item::Type(decl) => self.types.push(TypeDecl {
+					name: format!("{}{}", prefix, decl.name),
+					params: decl.params.clone(),
+					kind: match &decl.kind {
+						DeclKind::Struct(fields) => DeclKind::Struct(
+							fields.iter()
+								.map(|(name, typ)| (name.clone(), qualify(typ)))
+								.collect(),
+						),
+						DeclKind::Union(variants) => DeclKind::Union(
+							variants.iter()
+								.map(|(name, typ)| (name.clone(), typ.as_ref().map(qualify)))
+								.collect(),
+						),
+						DeclKind::Alias(typ) => DeclKind::Alias(qualify(typ)),
+					},
+				}),
+				Item::Mod(inner) => self.insert_types(inner, &prefix),
+				_ => {}
+			}
 		}
 	}
+	
+	pub fn find_type(&self, name: &str) -> EvalResult<&TypeDecl> {
+		// imported names stand for the path they were imported from
+		let path = self.imports.iter().rev()
+			.find(|(import, _)| import == name)
+			.map_or(name, |(_, path)| path.as_str());
+		
+		self.types.iter()
+			.find(|decl| decl.name == path)
+			.ok_or(EvalError::UnknownType(name.to_string()))
+	}
+	
+	/// Replaces every alias in a type with the type it stands for, and imported
+	/// names with their full path.
+	pub fn expand(&self, typ: &Type) -> EvalResult<Type> {
+		Ok(match typ {
+			Type::Decl(name, args) => {
+				let decl = self.find_type(name)?;
+				let args = args.iter()
+					.map(|arg| self.expand(arg))
+					.collect::<EvalResult<Vec<_>>>()?;
+				
+				match &decl.kind {
+					DeclKind::Alias(target) => {
+						let bindings: Vec<_> = decl.params.iter().cloned()
+							.zip(args)
+							.collect();
+						self.expand(&target.substitute(&bindings))?
+					}
+					_ => Type::Decl(decl.name.clone(), args),
+				}
+			}
+			Type::IndexOf(name) => {
+				let value = self.find_const(name)
+					.ok_or(EvalError::UnknownIdent(name.clone()))?;
+				match value.as_int().and_then(|n| u32::try_from(n).ok()) {
+					Some(n) => Type::Index(n),
+					None => Err(EvalError::TypeMismatch {
+						expected: Type::U32,
+						got: value.get_type(),
+					})?,
+				}
+			}
+			Type::Stack(elem) => Type::Stack(Box::new(self.expand(elem)?)),
+			Type::Fn(arg, ret) => Type::Fn(
+				Box::new(self.expand(arg)?),
+				Box::new(self.expand(ret)?),
+			),
+			Type::Tuple(elems) => Type::Tuple(
+				elems.iter()
+					.map(|elem| self.expand(elem))
+					.collect::<EvalResult<_>>()?,
+			),
+			Type::Proc(params) => Type::Proc(
+				params.iter()
+					.map(|(var, typ)| Ok((*var, self.expand(typ)?)))
+					.collect::<EvalResult<_>>()?,
+			),
+			_ => typ.clone(),
+		})
+	}
+}
+
+fn qualify_type(typ: &Type, prefix: &str, siblings: &[&str]) -> Type {
+	match typ {
+		Type::Decl(name, args) => Type::Decl(
+			match siblings.contains(&name.as_str()) {
+				true => format!("{}{}", prefix, name),
+				false => name.clone(),
+			},
+			args.iter().map(|arg| qualify_type(arg, prefix, siblings)).collect(),
+		),
+		Type::Stack(elem) => Type::Stack(Box::new(qualify_type(elem, prefix, siblings))),
+		Type::Fn(arg, ret) => Type::Fn(
+			Box::new(qualify_type(arg, prefix, siblings)),
+			Box::new(qualify_type(ret, prefix, siblings)),
+		),
+		Type::Tuple(elems) => Type::Tuple(
+			elems.iter().map(|elem| qualify_type(elem, prefix, siblings)).collect(),
+		),
+		Type::Proc(params) => Type::Proc(
+			params.iter()
+				.map(|(var, typ)| (*var, qualify_type(typ, prefix, siblings)))
+				.collect(),
+		),
+		_ => typ.clone(),
+	}
 }
 
 impl StackFrame {
 	pub fn new(items: Context, args: Vec<(String, Value)>) -> Self {
 		let (names, values) = args.into_iter()
 			.unzip(); // owo
-		Self { names, values, items }
+		Self { names, values, items, type_args: Vec::new() }
 	}
 	
 	// used when calling internal procedures
@@ -59,6 +264,12 @@ impl StackFrame {
 		&mut self.values
 	}
 	
+	/// Replaces the type parameters in a type with what they're bound to in
+	/// this call, and expands any aliases in it.
+	pub fn resolve_type(&self, typ: &Type) -> EvalResult<Type> {
+		self.items.expand(&typ.substitute(&self.type_args))
+	}
+	
 	pub fn into_inner(self) -> Vec<Value> {
 		self.values
 	}
@@ -85,84 +296,204 @@ impl StackFrame {
 		Ok(self.values.remove(idx))
 	}
 	
-	pub fn swap(&mut self, left: &str, right: &str) -> EvalResult<()> {
-		let left_idx = self.names.iter()
-			.rposition(|name| *name == left)
-			.ok_or(EvalError::UnknownIdent(left.to_string()))?;
-		let right_idx = self.names.iter()
-			.rposition(|name| *name == right)
-			.ok_or(EvalError::UnknownIdent(right.to_string()))?;
+	pub fn swap(&mut self, left: &LValue, right: &LValue) -> EvalResult<()> {
+		let left_val = self.get(left)?;
+		let right_val = self.get(right)?;
 		
 		// ensure types are the same
-		assert_eq!(
-			self.values[left_idx].get_type(),
-			self.values[right_idx].get_type(),
-			"tried to swap variables with different types"
-		);
+		if left_val.get_type() != right_val.get_type() {
+			return Err(EvalError::TypeMismatch {
+				expected: left_val.get_type(),
+				got: right_val.get_type(),
+			});
+		}
 		
-		self.values.swap(left_idx, right_idx);
+		self.set(left, right_val)?;
+		self.set(right, left_val)?;
 		
 		Ok(())
 	}
 	
-	// this function returns `Value` instead of `&Value` in order to allow us to
-	// do ~~ h a c k s ~~. See: `string.len`.
-	pub fn get(&self, deref_path: &LValue) -> EvalResult<Value> {
-		let pos = self.names.iter()
-			.rposition(|var_name| *var_name == deref_path.id)
-			.ok_or(EvalError::UnknownIdent(deref_path.id.clone()))?;
+	/// The variables in this frame, from the most recently declared to the
+	/// least. Shadowed variables come after the ones shadowing them.
+	pub fn vars(&self) -> impl Iterator<Item = (&String, &Value)> {
+		self.names.iter().zip(&self.values).rev()
+	}
+	
+	/// Gets the value of the variable with the given name, if there is one.
+	pub fn get_var(&self, id: &str) -> Option<&Value> {
+		let pos = self.names.iter().rposition(|var_name| var_name == id)?;
+		Some(&self.values[pos])
+	}
+	
+	fn position(&self, id: &str) -> EvalResult<usize> {
+		self.names.iter()
+			.rposition(|var_name| var_name == id)
+			.ok_or(EvalError::UnknownIdent(id.to_string()))
+	}
+	
+	fn eval_int(&self, expr: &Expr) -> EvalResult<i128> {
+		let value = expr.eval(self)?;
+		value.as_int().ok_or(EvalError::TypeMismatch {
+			expected: Type::U32,
+			got: value.get_type(),
+		})
+	}
+	
+	/// Evaluates the index in a `.(i)` deref, and checks that it's in bounds.
+	fn eval_index(&self, deref_path: &LValue, args: &[Expr], len: usize) -> EvalResult<usize> {
+		let i = self.eval_int(&args[0])?;
 		
-		let mut value = self.values[pos].clone();
+		usize::try_from(i).ok()
+			.filter(|&i| i < len)
+			.ok_or(EvalError::OutOfBounds {
+				index: i,
+				len,
+				span: deref_path.span.clone(),
+			})
+	}
+	
+	/// Evaluates the bounds of a `.(start..end)` slice. The end may be equal to
+	/// the length, but the start can't come after the end.
+	fn eval_range(&self, deref_path: &LValue, args: &[Expr], len: usize) -> EvalResult<Range<usize>> {
+		let start = self.eval_int(&args[0])?;
+		let end = self.eval_int(&args[1])?;
 		
-		for deref_op in &deref_path.ops {
-			value = match value {
-				Value::Stack(stack, _) => match deref_op {
-					// this *should* be a temporary hack for now. remove once
-					// stack values can store their length.
-					Deref { name: Some(field), args: None } if field == "len" =>
-						Value::U32(stack.len() as u32),
-					
-					Deref { name: None, args: Some(args) } =>
-						match args[0].eval(self)? {
-							Value::U32(i) =>
-								stack.get(i as usize).unwrap().clone(),
-							
-							value => panic!("tried to do {:?}.({})", stack, value),
-						}
-					
-					_ => todo!()
-				}
-				
-				Value::String(string) => match deref_op {
-					// this *should* be a temporary hack for now. remove once
-					// string values can store their length.
-					Deref {name: Some(field), args: None} if field == "len" =>
-						Value::U32(string.len() as u32),
-					
-					Deref {name: None, args: Some(args)} =>
-						match args[0].eval(self)? {
-							Value::U32(i) => match string.chars().nth(i as usize) {
-								Some(c) => c.into(),
-								None => panic!("XX str: {:?}, i: {}, len: {}", string, i, string.len()),
-							}
-							
-							value => todo!("{:?}.({:?})", string, value)
-						}
-					
-					_ => todo!()
-				}
-				
-				val => todo!("{} {:?}", val, deref_op)
-			};
+		let out_of_bounds = |index| EvalError::OutOfBounds {
+			index,
+			len,
+			span: deref_path.span.clone(),
+		};
+		if end < 0 || end > len as i128 {
+			return Err(out_of_bounds(end));
+		}
+		if start < 0 || start > end {
+			return Err(out_of_bounds(start));
 		}
+		Ok(start as usize..end as usize)
+	}
+	
+	pub fn get(&self, deref_path: &LValue) -> EvalResult<Value> {
+		let pos = self.position(&deref_path.id)?;
+		let value = self.deref(&self.values[pos], deref_path, &deref_path.ops)?;
+		Ok(value.into_owned())
+	}
+	
+	// follows the deref ops starting from `value`. elements are borrowed
+	// rather than copied, so that indexing into an array or string doesn't
+	// copy all of it. fields like `.len` have to be computed though.
+	fn deref<'a>(&self, value: &'a Value, deref_path: &LValue, ops: &[Deref]) -> EvalResult<Cow<'a, Value>> {
+		let (deref_op, rest) = match ops.split_first() {
+			Some(split) => split,
+			None => return Ok(Cow::Borrowed(value)),
+		};
 		
-		Ok(value)
+		let computed = match (value, deref_op) {
+			(Value::Stack(stack, _), Deref { name: None, args: Some(args) }) => {
+				let i = self.eval_index(deref_path, args, stack.len())?;
+				return self.deref(&stack[i], deref_path, rest);
+			}
+			(Value::Array(array), Deref { name: None, args: Some(args) }) => {
+				let i = self.eval_index(deref_path, args, array.len())?;
+				return self.deref(&array[i], deref_path, rest);
+			}
+			(Value::Struct(typ, fields), Deref { name: Some(field), args: None }) => {
+				let value = Value::field(fields, typ, field)?;
+				return self.deref(value, deref_path, rest);
+			}
+			(Value::Tuple(elems), Deref { name: Some(field), args: None }) => {
+				let value = Value::element(elems, field)?;
+				return self.deref(value, deref_path, rest);
+			}
+			
+			(Value::Stack(stack, _), Deref { name: Some(field), args: None }) =>
+				match field.as_str() {
+					"len" => Value::U32(stack.len() as u32),
+					"top" => stack.last().ok_or(EvalError::EmptyStack)?.clone(),
+					"empty" => Value::Bool(stack.is_empty()),
+					_ => return Err(no_deref(value, deref_op, deref_path)),
+				}
+			(Value::Array(array), Deref { name: Some(field), args: None })
+			if field == "len" =>
+				Value::U32(array.len() as u32),
+			(Value::String(string), Deref { name: Some(field), args: None })
+			if field == "len" =>
+				Value::U32(string.len() as u32),
+			
+			(Value::String(string), Deref { name: None, args: Some(args) }) => {
+				let i = self.eval_index(deref_path, args, string.len())?;
+				Value::Char(string.get(i).unwrap())
+			}
+			
+			// `.(start..end)`
+			(Value::String(string), Deref { name: Some(method), args: Some(args) })
+			if method == "slice" => {
+				let range = self.eval_range(deref_path, args, string.len())?;
+				Value::String(string.slice(range.start, range.end).unwrap())
+			}
+			(Value::Array(array), Deref { name: Some(method), args: Some(args) })
+			if method == "slice" => {
+				let range = self.eval_range(deref_path, args, array.len())?;
+				Value::Array(array[range].into())
+			}
+			
+			(val, deref_op) => return Err(no_deref(val, deref_op, deref_path)),
+		};
+		
+		Ok(Cow::Owned(self.deref(&computed, deref_path, rest)?.into_owned()))
+	}
+	
+	/// If the lvalue refers to a character within a string, returns the
+	/// path to the string and the index of the character in it.
+	pub fn string_index(&self, deref_path: &LValue) -> EvalResult<Option<(LValue, usize)>> {
+		let (args, init) = match deref_path.ops.split_last() {
+			Some((Deref { name: None, args: Some(args) }, init)) => (args, init),
+			_ => return Ok(None),
+		};
+		
+		let string_path = LValue {
+			id: deref_path.id.clone(),
+			ops: init.to_vec(),
+			span: deref_path.span.clone(),
+		};
+		let pos = self.position(&string_path.id)?;
+		match &*self.deref(&self.values[pos], &string_path, init)? {
+			Value::String(string) => {
+				let i = self.eval_index(deref_path, args, string.len())?;
+				Ok(Some((string_path, i)))
+			}
+			_ => Ok(None),
+		}
+	}
+	
+	/// Overwrites the value the lvalue refers to. Unlike `get_mut`, this also
+	/// works for characters within strings.
+	pub fn set(&mut self, deref_path: &LValue, value: Value) -> EvalResult<()> {
+		match self.string_index(deref_path)? {
+			Some((string_path, i)) => self.set_char(&string_path, i, value),
+			None => {
+				*self.get_mut(deref_path)? = value;
+				Ok(())
+			}
+		}
 	}
 	
+	/// Replaces the character at index `i` of the string at `string_path`.
+	pub fn set_char(&mut self, string_path: &LValue, i: usize, value: Value) -> EvalResult<()> {
+		match (self.get_mut(string_path)?, value) {
+			(Value::String(string), Value::Char(c)) => string.set(i, c).unwrap(),
+			(_, value) => Err(EvalError::TypeMismatch {
+				expected: Type::Char,
+				got: value.get_type(),
+			})?,
+		}
+		Ok(())
+	}
+	
+	// characters within strings aren't stored as values of their own, so they
+	// can't be borrowed here. use `set` for those instead.
 	pub fn get_mut(&mut self, deref_path: &LValue) -> EvalResult<&mut Value> {
-		let pos = self.names.iter()
-			.rposition(|var_name| *var_name == deref_path.id)
-			.ok_or(EvalError::UnknownIdent(deref_path.id.clone()))?;
+		let pos = self.position(&deref_path.id)?;
 		
 		// v important variables. `clone` is here because we can't borrow the
 		// stack frame while it's already mutably borrowed. `value` cannot be
@@ -174,20 +505,46 @@ impl StackFrame {
 		for deref in &deref_path.ops {
 			match (value, deref) {
 				(Value::Array(array),
-				Deref { name: None, args: Some(args) }) =>
-					match args[0].eval(&clone)? {
-						Value::U32(idx) => {
-							value = &mut array[idx as usize];
-						}
-						value => return Err(EvalError::TypeMismatch {
-							expected: Type::U32,
-							got: value.get_type(),
-						}),
-					}
-				_ => todo!()
+				Deref { name: None, args: Some(args) }) => {
+					let i = clone.eval_index(deref_path, args, array.len())?;
+					value = &mut array[i];
+				}
+				(Value::Stack(stack, _),
+				Deref { name: None, args: Some(args) }) => {
+					let i = clone.eval_index(deref_path, args, stack.len())?;
+					value = &mut stack[i];
+				}
+				(Value::Struct(typ, fields),
+				Deref { name: Some(field), args: None }) => {
+					let typ = typ.clone();
+					value = fields.iter_mut()
+						.find(|(name, _)| name == field)
+						.map(|(_, value)| value)
+						.ok_or(EvalError::NoField { typ, field: field.clone() })?;
+				}
+				(Value::Tuple(elems),
+				Deref { name: Some(field), args: None }) => {
+					Value::element(elems, field)?;
+					value = &mut elems[field.parse::<usize>().unwrap()];
+				}
+				(val, deref) => return Err(no_deref(val, deref, deref_path)),
 			}
 		}
 		
 		Ok(value)
 	}
 }
+
+// for derefs that don't make sense on the type of value they're used on
+fn no_deref(value: &Value, deref: &Deref, deref_path: &LValue) -> EvalError {
+	let deref = match deref {
+		Deref { name: Some(method), args: Some(_) } => format!("method {:?}", method),
+		Deref { name: Some(field), args: None } => format!("field {:?}", field),
+		Deref { name: None, .. } => "elements to index".to_string(),
+	};
+	EvalError::NoDeref {
+		typ: value.get_type(),
+		deref,
+		span: deref_path.span.clone(),
+	}
+}
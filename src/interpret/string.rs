@@ -0,0 +1,128 @@
+/*!
+Strings for the `Str` type. These are indexed by character rather than by byte,
+so each string keeps track of where its characters start in order to find any
+one of them in constant time.
+*/
+
+use std::fmt;
+
+/// A UTF-8 string, along with the byte offset of each of its characters.
+#[derive(Debug, Clone, Default)]
+pub struct Str {
+	text: String,
+	starts: Vec<usize>,
+}
+
+impl Str {
+	pub fn as_str(&self) -> &str {
+		&self.text
+	}
+
+	/// The number of characters in the string.
+	pub fn len(&self) -> usize {
+		self.starts.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.starts.is_empty()
+	}
+
+	// byte offset of the character at the given index, or of the end of the
+	// string if the index is past the last character.
+	fn start(&self, i: usize) -> usize {
+		self.starts.get(i).copied().unwrap_or(self.text.len())
+	}
+
+	pub fn get(&self, i: usize) -> Option<char> {
+		let start = *self.starts.get(i)?;
+		self.text[start..].chars().next()
+	}
+
+	/// Replaces the character at the given index. Characters after it only need
+	/// to be moved if the new character has a different width in UTF-8.
+	pub fn set(&mut self, i: usize, c: char) -> Option<()> {
+		let start = *self.starts.get(i)?;
+		let end = self.start(i + 1);
+
+		let mut buf = [0; 4];
+		let new = c.encode_utf8(&mut buf);
+		self.text.replace_range(start..end, new);
+
+		if new.len() != end - start {
+			for offset in &mut self.starts[i + 1..] {
+				*offset = *offset + new.len() - (end - start);
+			}
+		}
+		Some(())
+	}
+
+	/// Copies the characters from `start` up to (but not including) `end`.
+	pub fn slice(&self, start: usize, end: usize) -> Option<Str> {
+		if start > end || end > self.len() {
+			return None;
+		}
+
+		Some(Str::from(&self.text[self.start(start)..self.start(end)]))
+	}
+
+	pub fn push_str(&mut self, s: &str) {
+		let base = self.text.len();
+		self.starts.extend(s.char_indices().map(|(i, _)| base + i));
+		self.text.push_str(s);
+	}
+
+	/// Removes the suffix from the end of the string, if the string ends with
+	/// it. Returns whether it did.
+	pub fn strip_suffix(&mut self, suffix: &str) -> bool {
+		if !self.text.ends_with(suffix) {
+			return false;
+		}
+
+		let len = self.text.len() - suffix.len();
+		self.text.truncate(len);
+		while self.starts.last().is_some_and(|&start| start >= len) {
+			self.starts.pop();
+		}
+		true
+	}
+
+	/// Rotates the characters of the string to the left, wrapping around its
+	/// length. Negative amounts rotate to the right.
+	pub fn rotate_left(&mut self, amount: i128) {
+		if self.is_empty() {
+			return;
+		}
+
+		let split = self.start(amount.rem_euclid(self.len() as i128) as usize);
+		let mut text = self.text.split_off(split);
+		text.push_str(&self.text);
+		*self = Str::from(text);
+	}
+}
+
+impl From<String> for Str {
+	fn from(text: String) -> Str {
+		let starts = text.char_indices().map(|(i, _)| i).collect();
+		Str { text, starts }
+	}
+}
+
+impl From<&str> for Str {
+	fn from(text: &str) -> Str {
+		Str::from(text.to_string())
+	}
+}
+
+impl PartialEq for Str {
+	fn eq(&self, other: &Str) -> bool {
+		self.text == other.text
+	}
+}
+
+impl Eq for Str {}
+
+impl fmt::Display for Str {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.text)
+	}
+}
@@ -1,17 +1,38 @@
 use std::fmt;
 
-use crate::ast::{Module, Deref};
-use crate::interpret::{EvalResult, EvalError, StackFrame};
+use std::rc::Rc;
+
+use crate::ast::{Closure, Module, Deref};
+use crate::interpret::{BigInt, Str, EvalResult, EvalError, StackFrame};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
 	Nil,
 	Bool(bool),
+	I8(i8),
+	I16(i16),
+	I32(i32),
+	I64(i64),
+	U8(u8),
+	U16(u16),
 	U32(u32),
-	String(String),
+	U64(u64),
+	Int(BigInt),
+	/// a value and the size of its index type
+	Index(u32, u32),
+	Char(char),
+	String(Str),
 	Stack(Vec<Self>, Type),
 	Array(Box<[Value]>),
-	//Proc(Path),
+	Tuple(Vec<Self>),
+	/// a value of a struct type, with its fields in declared order
+	Struct(Type, Vec<(String, Self)>),
+	/// a value of a union type: which variant it is, and the variant's value
+	Union(Type, String, Option<Box<Self>>),
+	/// a procedure, by its name and type
+	Proc(String, Type),
+	/// a function, which may have captured some values
+	Fn(Rc<Closure>),
 }
 
 use crate::ast::Type;
@@ -19,15 +40,216 @@ impl Value {
 	pub fn get_type(&self) -> Type {
 		match self {
 			//Value::Nil       => Type::Nil,
-			Value::Bool(_)   => Type::U32,
+			Value::Bool(_)   => Type::Bool,
+			Value::I8(_)     => Type::I8,
+			Value::I16(_)    => Type::I16,
+			Value::I32(_)    => Type::I32,
+			Value::I64(_)    => Type::I64,
+			Value::U8(_)     => Type::U8,
+			Value::U16(_)    => Type::U16,
 			Value::U32(_)    => Type::U32,
+			Value::U64(_)    => Type::U64,
+			Value::Int(_)    => Type::Int,
+			Value::Index(_, n) => Type::Index(*n),
+			Value::Char(_)   => Type::Char,
 			Value::String(_) => Type::String,
-			Value::Stack(..)  => Type::Stack(Box::new(Type::U32)),
+			Value::Stack(_, elem) => Type::Stack(Box::new(elem.clone())),
+			// arrays are functions from indices to their elements
+			Value::Array(array) => Type::Fn(
+				Box::new(Type::Index(array.len() as u32)),
+				Box::new(array.first().map_or(Type::Infer, Value::get_type)),
+			),
+			Value::Tuple(elems) => Type::Tuple(elems.iter().map(Value::get_type).collect()),
+			Value::Struct(typ, _) | Value::Union(typ, ..)
+			| Value::Proc(_, typ) => typ.clone(),
+			Value::Fn(closure) => closure.func.get_type(),
 			
 			_ => todo!()
 		}
 	}
 	
+	/// Gets the value of a fixed-size integer, widened so that integers of any
+	/// size or signedness can be worked with the same way.
+	pub fn as_int(&self) -> Option<i128> {
+		Some(match *self {
+			Value::I8(n)  => n as i128,
+			Value::I16(n) => n as i128,
+			Value::I32(n) => n as i128,
+			Value::I64(n) => n as i128,
+			Value::U8(n)  => n as i128,
+			Value::U16(n) => n as i128,
+			Value::U32(n) => n as i128,
+			Value::U64(n) => n as i128,
+			Value::Index(n, _) => n as i128,
+			_ => return None,
+		})
+	}
+	
+	/// Creates an integer of the given type, wrapping the value around if it
+	/// doesn't fit.
+	pub fn wrapping_int(n: i128, typ: &Type) -> Option<Value> {
+		Some(match typ {
+			Type::I8  => Value::I8(n as i8),
+			Type::I16 => Value::I16(n as i16),
+			Type::I32 => Value::I32(n as i32),
+			Type::I64 => Value::I64(n as i64),
+			Type::U8  => Value::U8(n as u8),
+			Type::U16 => Value::U16(n as u16),
+			Type::U32 => Value::U32(n as u32),
+			Type::U64 => Value::U64(n as u64),
+			// the empty type has no values to wrap around to
+			Type::Index(0) => return None,
+			Type::Index(size) => Value::Index(n.rem_euclid(*size as i128) as u32, *size),
+			_ => return None,
+		})
+	}
+	
+	/// Creates an integer of the given type, or gives `None` if the value
+	/// doesn't fit in it.
+	pub fn exact_int(n: i128, typ: &Type) -> Option<Value> {
+		Value::wrapping_int(n, typ)
+			.filter(|val| val.as_int() == Some(n))
+	}
+	
+	/// Rotates the bits of a fixed-size integer to the left by the given
+	/// amount. Negative amounts rotate to the right.
+	/// 
+	/// Index types don't have bits, so they rotate around their values instead,
+	/// where rotating left moves toward 0 and wraps around to N-1.
+	pub fn rotate_left(&self, amount: i128) -> Option<Value> {
+		if let Value::Index(n, size) = *self {
+			return Value::wrapping_int(n as i128 - amount, &Type::Index(size));
+		}
+		
+		let typ = self.get_type();
+		let bits = typ.bits()? as i128;
+		let mask = u128::MAX >> (128 - bits);
+		
+		let n = self.as_int()? as u128 & mask;
+		let amount = amount.rem_euclid(bits) as u32;
+		let rotated = (n << amount | n >> (bits as u32 - amount)) & mask;
+		
+		Value::wrapping_int(rotated as i128, &typ)
+	}
+	
+	/// Converts this value to the given type, as in an `as` expression.
+	pub fn cast(self, to: &Type) -> EvalResult<Value> {
+		let lossy = |value| EvalError::LossyCast { value: Box::new(value), to: to.clone() };
+		
+		Ok(match (self, to) {
+			// big integers go through the biggest fixed-size type they fit in
+			(Value::Int(n), to) if *to != Type::Int => {
+				let small = n.to_i128().and_then(|small| {
+					Value::exact_int(small, &Type::I64)
+						.or_else(|| Value::exact_int(small, &Type::U64))
+				});
+				match small {
+					Some(small) => small.cast(to).map_err(|_| lossy(Value::Int(n)))?,
+					None => Err(lossy(Value::Int(n)))?,
+				}
+			}
+			(Value::Bool(b), Type::Int) => Value::Int(BigInt::from(b as i128)),
+			(Value::Char(c), Type::Int) => Value::Int(BigInt::from(c as i128)),
+			(value, Type::Int) if value.as_int().is_some() =>
+				Value::Int(BigInt::from(value.as_int().unwrap())),
+			
+			(Value::Bool(b), to) if to.is_int() => match Value::exact_int(b as i128, to) {
+				Some(n) => n,
+				None => Err(lossy(Value::Bool(b)))?,
+			}
+			(Value::Char(c), to) if to.is_int() => match Value::exact_int(c as i128, to) {
+				Some(n) => n,
+				None => Err(lossy(Value::Char(c)))?,
+			}
+			(value, to) if to.is_int() && value.as_int().is_some() => {
+				match Value::exact_int(value.as_int().unwrap(), to) {
+					Some(n) => n,
+					None => Err(lossy(value))?,
+				}
+			}
+			
+			(value, Type::Bool) if value.as_int() == Some(0) => Value::Bool(false),
+			(value, Type::Bool) if value.as_int() == Some(1) => Value::Bool(true),
+			
+			(value, Type::Char) if value.as_int().is_some() => {
+				let c = u32::try_from(value.as_int().unwrap()).ok()
+					.and_then(char::from_u32);
+				match c {
+					Some(c) => Value::Char(c),
+					None => Err(lossy(value))?,
+				}
+			}
+			
+			(Value::Char(c), Type::String) => Value::String(Str::from(c.to_string())),
+			(Value::String(s), Type::Char) => {
+				match (s.len(), s.get(0)) {
+					(1, Some(c)) => Value::Char(c),
+					_ => Err(lossy(Value::String(s)))?,
+				}
+			}
+			
+			(value, to) if value.get_type() == *to => value,
+			
+			(value, to) if value.get_type().can_cast_to(to) => Err(lossy(value))?,
+			(value, to) => Err(EvalError::TypeMismatch {
+				expected: to.clone(),
+				got: value.get_type(),
+			})?,
+		})
+	}
+	
+	/// Gives the zero value with the same shape as this one. This is what a
+	/// variable is left as after `push` moves its value onto a stack.
+	pub fn zeroed(&self) -> Value {
+		match self {
+			Value::Nil       => Value::Nil,
+			Value::Bool(_)   => Value::Bool(false),
+			Value::Int(_)    => Value::Int(BigInt::default()),
+			Value::Char(_)   => Value::Char('\0'),
+			Value::String(_) => Value::String(Str::default()),
+			Value::Stack(_, elem) => Value::Stack(Vec::new(), elem.clone()),
+			Value::Proc(..) | Value::Fn(_) => self.clone(),
+			Value::Array(array) =>
+				Value::Array(array.iter().map(Value::zeroed).collect()),
+			Value::Tuple(elems) =>
+				Value::Tuple(elems.iter().map(Value::zeroed).collect()),
+			Value::Struct(typ, fields) => Value::Struct(
+				typ.clone(),
+				fields.iter()
+					.map(|(name, value)| (name.clone(), value.zeroed()))
+					.collect(),
+			),
+			// unions have no variant that's more zero than the others
+			Value::Union(typ, variant, value) => Value::Union(
+				typ.clone(),
+				variant.clone(),
+				value.as_ref().map(|value| Box::new(value.zeroed())),
+			),
+			val => Value::wrapping_int(0, &val.get_type()).unwrap(),
+		}
+	}
+	
+	/// Finds an element of a tuple value by its position, like `.0`.
+	pub fn element<'a>(elems: &'a [Value], field: &str) -> EvalResult<&'a Value> {
+		field.parse::<usize>().ok()
+			.and_then(|i| elems.get(i))
+			.ok_or_else(|| EvalError::NoField {
+				typ: Type::Tuple(elems.iter().map(Value::get_type).collect()),
+				field: field.to_string(),
+			})
+	}
+	
+	/// Finds a field in the fields of a struct value.
+	pub fn field<'a>(fields: &'a [(String, Value)], typ: &Type, field: &str) -> EvalResult<&'a Value> {
+		fields.iter()
+			.find(|(name, _)| name == field)
+			.map(|(_, value)| value)
+			.ok_or(EvalError::NoField {
+				typ: typ.clone(),
+				field: field.to_string(),
+			})
+	}
+	
 	pub fn swap(&mut self, val: &mut Value) -> EvalResult<()> {
 		// check that types are the same.
 		if self.get_type() != val.get_type() {
@@ -48,6 +270,15 @@ impl fmt::Display for Value {
 			Value::Nil => fmt.write_str("Nil"),
 			
 			Value::Bool(b) => fmt.write_str(if *b { "True" } else { "False" }),
+			Value::I8(i)  => i.fmt(fmt),
+			Value::I16(i) => i.fmt(fmt),
+			Value::I32(i) => i.fmt(fmt),
+			Value::I64(i) => i.fmt(fmt),
+			Value::U8(i)  => i.fmt(fmt),
+			Value::U16(i) => i.fmt(fmt),
+			Value::U64(i) => i.fmt(fmt),
+			Value::Int(i) => i.fmt(fmt),
+			Value::Index(i, _) => i.fmt(fmt),
 			Value::U32(i) => i.fmt(fmt), /*{
 				// TODO modify this to show bijective numerals
 				let mut bij_repr = Vec::new();
@@ -91,8 +322,8 @@ impl fmt::Display for Value {
 				}
 			}*/
 			
-			//Value::Char(c)   => write!(fmt, "{:?}", c),
-			Value::String(s) => write!(fmt, "{:?}", s),
+			Value::Char(c)   => write!(fmt, "{:?}", c),
+			Value::String(s) => write!(fmt, "{:?}", s.as_str()),
 			
 			Value::Stack(stack, _) => {
 				fmt.write_str("[")?;
@@ -102,7 +333,51 @@ impl fmt::Display for Value {
 				fmt.write_str(">")
 			}
 			
-			Value::Array(_) => todo!(),
+			Value::Array(array) => {
+				fmt.write_str("[")?;
+				for (i, value) in array.iter().enumerate() {
+					if i > 0 {
+						fmt.write_str(", ")?;
+					}
+					write!(fmt, "{}", value)?;
+				}
+				fmt.write_str("]")
+			}
+			
+			Value::Struct(typ, fields) => {
+				if let Type::Decl(name, _) = typ {
+					write!(fmt, "{} ", name)?;
+				}
+				fmt.write_str("{")?;
+				for (i, (name, value)) in fields.iter().enumerate() {
+					if i > 0 {
+						fmt.write_str(", ")?;
+					}
+					write!(fmt, "{}: {}", name, value)?;
+				}
+				fmt.write_str("}")
+			}
+			
+			Value::Tuple(elems) => {
+				fmt.write_str("(")?;
+				for (i, value) in elems.iter().enumerate() {
+					if i > 0 {
+						fmt.write_str(", ")?;
+					}
+					write!(fmt, "{}", value)?;
+				}
+				if elems.len() == 1 {
+					fmt.write_str(",")?;
+				}
+				fmt.write_str(")")
+			}
+			
+			Value::Proc(name, _) => fmt.write_str(name),
+			Value::Fn(closure) => fmt.write_str(&closure.func.name),
+			
+			Value::Union(_, variant, None) => fmt.write_str(variant),
+			Value::Union(_, variant, Some(value)) =>
+				write!(fmt, "{}({})", variant, value),
 		}
 	}
 }
@@ -119,7 +394,7 @@ impl From<bool> for Value {
 
 impl From<char> for Value {
 	#[inline]
-	fn from(c: char) -> Self { Value::U32(c as u32) }
+	fn from(c: char) -> Self { Value::Char(c) }
 }
 
 impl From<u32> for Value {
@@ -129,5 +404,5 @@ impl From<u32> for Value {
 
 impl From<String> for Value {
 	#[inline]
-	fn from(s: String) -> Self { Value::String(s) }
+	fn from(s: String) -> Self { Value::String(Str::from(s)) }
 }
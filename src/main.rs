@@ -34,6 +34,8 @@ mod ast;
 //mod compile;
 mod interpret;
 mod repl;
+#[cfg(test)]
+mod tests;
 
 
 /// Test.
@@ -53,9 +55,6 @@ struct Args {
 fn main() -> io::Result<()> {
 	let args = Args::parse();
 	
-	repl::init()?;
-	
-	/*
 	match args.file {
 		// start REPL
 		None => repl::init()?,
@@ -86,8 +85,7 @@ fn main() -> io::Result<()> {
 			use std::fs::read_to_string as open;
 			
 			let source = open(path)?;
-			let tokens = Token::lexer(&source);
-			let mut parser = ast::Parser::new(tokens);
+			let mut parser = ast::Parser::new(&source);
 			
 			let ast = match parser.parse_file_module() {
 				Ok(ast) => ast,
@@ -101,18 +99,37 @@ fn main() -> io::Result<()> {
 						parser.line(),
 						parser.column()
 					);
-					eprintln!("rever: Expected {}, got {:?}", e, parser.slice());
+					eprintln!("rever: {}, got {:?}", e, parser.slice());
 					eprintln!("rever: Remaining source:\n{}", parser.remainder());
 					
 					return Ok(())
 				}
 			};
 			
+			let report = |kind: &str, e: interpret::EvalError| match e.span() {
+				Some(span) => {
+					let before = &source[..span.start];
+					let line = before.matches('\n').count() + 1;
+					let column = span.start - before.rfind('\n').map_or(0, |nl| nl + 1) + 1;
+					eprintln!(
+						"rever: {} at line {}, column {}: {}.",
+						kind, line, column, e
+					);
+				}
+				None => eprintln!("rever: {}: {}.", kind, e),
+			};
+			
 //			println!("{:#?}", ast);
-			interpret::interpret_file(ast.into());
+			// errors found before running are told apart from ones that
+			// happen while running
+			match interpret::load_file(ast) {
+				Ok(root) => if let Err(e) = interpret::interpret_file(root) {
+					report("Runtime error", e);
+				}
+				Err(e) => report("Check error", e),
+			}
 		}
 	}
-	*/
 	
 	Ok(())
 }
@@ -3,7 +3,7 @@ use logos::Logos;
 
 use crate::token::Token;
 use crate::ast::{self, LValue, Expr, Item, Module, Procedure, Param, Stmt, Type};
-use crate::interpret::{EvalResult, Stack, StackFrame, Context, Value};
+use crate::interpret::{EvalError, EvalResult, Stack, StackFrame, Context, Value};
 
 pub fn init() -> io::Result<()> {
 	let stdin = io::stdin();
@@ -12,26 +12,13 @@ pub fn init() -> io::Result<()> {
 	let mut continuing = false;
 	
 	let items = Context {
-		procs: vec![
-			Procedure {
-				name: "print".into(),
-				params: vec![
-					Param {
-						name: "msg".into(),
-						constant: false,
-						typ: Type::String,
-					},
-					Param {
-						name: "bytes_read".into(),
-						constant: false,
-						typ: Type::U32,
-					},
-				],
-				code: crate::interpret::intrinsic::PRINT_PROCDEF.clone(),
-			},
-		],
+		procs: crate::interpret::intrinsic::procs(),
 		funcs: vec![],
 		mods: vec![],
+		types: vec![],
+		imports: vec![],
+		consts: vec![],
+		pending_consts: vec![],
 	};
 	
 	let mut stack = Stack::new();
@@ -53,7 +40,7 @@ pub fn init() -> io::Result<()> {
 		
 		let line = match parser.parse_repl_line() {
 			Ok(line) => line,
-			Err(_) if parser.peek() == None => {
+			Err(_) if parser.peek().is_none() => {
 				continuing = true;
 				continue;
 			}
@@ -115,7 +102,7 @@ impl ast::Parser<'_> {
 				ReplLine::Show(LValue { id: name, ops: Vec::new() })
 			}
 			*/
-			Some(Token::Fn | Token::Proc | Token::Mod) =>
+			Some(Token::Fn | Token::Proc | Token::Mod | Token::Struct | Token::Union | Token::Alias | Token::Use | Token::Const) =>
 				self.parse_item()?.into(),
 			
 			Some(Token::Var) => {
@@ -199,18 +186,25 @@ impl ReplLine {
 		match self {
 			ReplLine::Blank => Ok(Value::Nil),
 			
-			ReplLine::Var(name, _, expr) => {
-				let val = expr.eval(ctx)?;
+			ReplLine::Var(name, typ, expr) => {
+				let val = expr.eval_as(ctx, &typ)?;
+				if typ != Type::Infer && val.get_type() != typ {
+					return Err(EvalError::TypeMismatch {
+						expected: typ,
+						got: val.get_type(),
+					});
+				}
 				ctx.push(name, val);
 				Ok(Value::Nil)
 			}
 			
 			ReplLine::Drop(name, _, opt_deinit) => {
+				let val = ctx.remove(&name)?;
 				match opt_deinit {
-					None => Ok(ctx.remove(&name)?),
+					None => Ok(val),
 					Some(expr) => {
-						let deinit = expr.eval(ctx)?;
-						assert_eq!(deinit, ctx.remove(&name)?);
+						let deinit = expr.eval_as(ctx, &val.get_type())?;
+						assert_eq!(deinit, val);
 						Ok(Value::Nil)
 					}
 				}
@@ -218,6 +212,7 @@ impl ReplLine {
 			
 			ReplLine::Item(item) => {
 				ctx.items.insert(item);
+				ctx.items.eval_consts()?;
 				Ok(Value::Nil)
 			}
 			ReplLine::Stmt(stmt) => {
@@ -10,7 +10,7 @@ pub struct Span {
 impl Span {
 	pub fn new(start: usize, len: usize) -> Span {
 		Span {
-			start: start,
+			start,
 			end: start + len,
 		}
 	}
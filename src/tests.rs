@@ -0,0 +1,495 @@
+/*!
+Runs the programs in `tests/roundtrip`, along with smaller programs written
+inline. Each roundtrip program calls some procedures forwards, checks the
+results with `drop`, then undoes them and checks that the original values come
+back. The examples are only loaded and checked, since some of them read input.
+*/
+
+use crate::ast::{Parser, Type, CALLS};
+use crate::interpret::{self, EvalError, EvalResult};
+
+fn run(source: &str) -> EvalResult<()> {
+	let mut parser = Parser::new(source);
+	let items = match parser.parse_file_module() {
+		Ok(items) => items,
+		Err(e) => panic!("parser error at line {}: {}", parser.line(), e),
+	};
+	interpret::interpret_file(interpret::load_file(items)?)
+}
+
+#[test]
+fn mul_div() {
+	run(include_str!("../tests/roundtrip/mul_div.rvr")).unwrap();
+}
+
+#[test]
+fn mul_by_even() {
+	let source = "proc main\n\tvar x := 3\n\tx *= 2\n\tdrop x := 6\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+}
+
+#[test]
+fn index() {
+	run(include_str!("../tests/roundtrip/index.rvr")).unwrap();
+}
+
+// 5 has no inverse modulo 10
+#[test]
+fn index_mul_by_non_unit() {
+	let source = "proc main\n\tvar x: 0A := 3\n\tx *= 5\n\tdrop x := 5\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+}
+
+// 3 xor 4 = 7, which would wrap around to 2 and then xor back to 6
+#[test]
+fn index_xor_out_of_range() {
+	let source = "proc main\n\tvar x: 05 := 3\n\tx := 4\n\tx := 4\n\tdrop x := 3\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::OutOfRange { value: 7, .. })));
+	
+	let source = "proc main\n\tvar x: 05 := 3\n\tvar y: 05 := x or 4\n\tdrop y := 2\n\tdrop x := 3\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::OutOfRange { value: 7, .. })));
+}
+
+#[test]
+fn cswap() {
+	run(include_str!("../tests/roundtrip/cswap.rvr")).unwrap();
+}
+
+#[test]
+fn cswap_condition() {
+	// the condition would be false after swapping, so it can't be undone
+	let source = "proc main\n\tvar a := 5\n\tvar b := 1\n\tcswap a > b, a, b\n\tdrop b := 5\n\tdrop a := 1\nreturn\n";
+	assert!(Parser::new(source).parse_file_module().is_err());
+	
+	let source = "proc main\n\tvar n := 1\n\tvar a := 5\n\tvar b := 1\n\tcswap n, a, b\n\tdrop b := 1\n\tdrop a := 5\n\tdrop n := 1\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::Bool, got: Type::U32 })
+	));
+}
+
+#[test]
+fn from_var() {
+	run(include_str!("../tests/roundtrip/from_var.rvr")).unwrap();
+}
+
+#[test]
+fn wrong_drop_value() {
+	let source = "proc main\n\tvar x := 3\n\tx += 1\n\tdrop x := 3\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+}
+
+#[test]
+fn conditions_are_bools() {
+	let source = "proc main\n\tvar x := 3\n\tif x\n\t\tx += 1\n\tfi x\n\tdrop x := 4\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::Bool, got: Type::U32 })
+	));
+	
+	// the assertion would send the undo down the other branch
+	let source = "proc main\n\tvar x := 3\n\tif x = 3\n\t\tx += 1\n\tfi x = 3\n\tdrop x := 4\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+}
+
+#[test]
+fn rotate_bool() {
+	let source = "proc main\n\tvar b := True\n\tb :< 1\n\tdrop b := True\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Bool })
+	));
+}
+
+// runs an `if` on `y = 0` that has to take its first branch
+fn branch(test: &str) -> EvalResult<()> {
+	run(&format!(
+		"proc main\n\tvar y := 0\n\tvar x := 0\n\tif {}\n\t\tx += 1\n\tfi x = 1\n\tdrop x := 1\n\tdrop y := 0\nreturn\n",
+		test,
+	))
+}
+
+#[test]
+fn and_or_short_circuit() {
+	branch("(y = 0) or (1 / y = 1)").unwrap();
+	branch("not ((y != 0) and (1 / y = 1))").unwrap();
+}
+
+#[test]
+fn conditions_dont_short_circuit() {
+	assert!(matches!(branch("y = 0; 1 / y = 1"), Err(EvalError::DivideByZero)));
+	assert!(matches!(branch("y = 0, 1 / y = 0"), Err(EvalError::DivideByZero)));
+	
+	// `and` and `or` still short-circuit inside of them
+	branch("y = 0; (y != 0) and (1 / y = 1)").unwrap();
+	branch("y = 0, (y = 0) or (1 / y = 1)").unwrap();
+}
+
+// `,` binds tighter than `;`
+#[test]
+fn condition_precedence() {
+	branch("y != 0, y = 0; y = 0").unwrap();
+	branch("y = 0; y = 0, y != 0").unwrap();
+}
+
+// the middle operands are each compared twice, but only evaluated once
+#[test]
+fn chain_evaluates_once() {
+	let source = "\
+fn f(x: U32): U32 = x
+
+proc main
+	var r := 0 < f(1) <= f(2) < 3
+	drop r := True
+return
+";
+	CALLS.with(|calls| calls.set(0));
+	run(source).unwrap();
+	assert_eq!(CALLS.with(|calls| calls.get()), 2);
+}
+
+#[test]
+fn stack() {
+	run(include_str!("../tests/roundtrip/stack.rvr")).unwrap();
+}
+
+#[test]
+fn stack_errors() {
+	// the element type is set by the first push
+	let source = "proc main\n\tvar s := nil\n\tvar x := 1\n\tvar c := 'a'\n\tdo push {x, s}\n\tdo push {c, s}\n\tdrop c := '\\0'\n\tdrop x := 0\n\tdrop s := nil\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Char })
+	));
+	
+	// popping into a non-zero value would lose it
+	let source = "proc main\n\tvar s: Stack[U32] := nil\n\tvar x := 1\n\tdo push {x, s}\n\tx += 2\n\tdo pop {x, s}\n\tdrop x := 1\n\tdrop s := nil\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+	
+	let source = "proc main\n\tvar s: Stack[U32] := nil\n\tvar x := 0\n\tdo pop {x, s}\n\tdrop x := 0\n\tdrop s := nil\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::EmptyStack)));
+	
+	// undoing a push pops
+	let source = "proc main\n\tvar s: Stack[U32] := nil\n\tvar x := 0\n\tundo push {x, s}\n\tdrop x := 0\n\tdrop s := nil\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::EmptyStack)));
+}
+
+#[test]
+fn array() {
+	run(include_str!("../tests/roundtrip/array.rvr")).unwrap();
+}
+
+#[test]
+fn array_out_of_bounds() {
+	let source = "proc main\n\tvar a: 03 -> U32 := [1, 2, 3]\n\ta.(3) += 1\n\tdrop a := [1, 2, 3]\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::OutOfBounds { index: 3, len: 3, .. })));
+}
+
+#[test]
+fn index_non_array() {
+	let source = "proc main\n\tvar x := 1\n\tx.(0) += 1\n\tdrop x := 1\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::NoDeref { typ: Type::U32, .. })));
+	
+	// unfinished indices are parse errors, not panics
+	for index in ["x.(1 2)", "x.(1"] {
+		let source = format!("proc main\n\tvar x := 1\n\t{index}");
+		assert!(Parser::new(&source).parse_file_module().is_err());
+	}
+}
+
+#[test]
+fn string() {
+	run(include_str!("../tests/roundtrip/string.rvr")).unwrap();
+}
+
+// indices count characters, not bytes
+#[test]
+fn string_out_of_bounds() {
+	let source = "proc main\n\tvar s := \"ééé\"\n\ts.(3) += 1\n\tdrop s := \"ééé\"\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::OutOfBounds { index: 3, len: 3, .. })));
+}
+
+// the checks run before anything does, so these never get to `main`
+fn check(source: &str) -> EvalResult<()> {
+	let items = Parser::new(source).parse_file_module().unwrap();
+	interpret::load_file(items).map(|_| ())
+}
+
+#[test]
+fn fn_sees_only_params() {
+	let source = "fn f(x: U32): U32 = x + y\n\nproc main\n\tvar y := 1\n\tdrop y := 1\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::OutOfScope { name, .. }) if name == "y"));
+}
+
+#[test]
+fn fn_cant_use_procs() {
+	let source = "fn f(x: U32): U32\n\tlet g = main\n\tx\n\nproc main\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::ImpureCall { name, .. }) if name == "main"));
+}
+
+#[test]
+fn closures_capture_proc_vars() {
+	let source = "\
+fn apply(f: U32 -> U32, x: U32): U32 = f(x)
+
+proc main
+	var n := 5
+	var r := apply(fn(x) = x + n, 2)
+	drop r := 7
+	drop n := 5
+return
+";
+	check(source).unwrap();
+}
+
+#[test]
+fn closures() {
+	run(include_str!("../tests/roundtrip/closures.rvr")).unwrap();
+}
+
+#[test]
+fn closure_drop_compares_captures() {
+	let source = "\
+fn adder(n: U32): U32 -> U32 = fn(x) = x + n
+
+proc main
+	var n := 3
+	var add := adder(n)
+	n += 1
+	drop add := adder(n)
+	n -= 1
+	drop n := 3
+return
+";
+	assert!(matches!(run(source), Err(EvalError::IrreversibleState)));
+}
+
+#[test]
+fn call_arity() {
+	let source = "fn f(a: U32, b: U32): U32 = a + b\n\nproc main\n\tvar x := f(1)\n\tdrop x := 1\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::ArgCount { expected: 2, got: 1, .. })));
+	
+	let source = "proc main\n\tdo nosuch(1)\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::OutOfScope { name, .. }) if name == "nosuch"));
+}
+
+#[test]
+fn const_order() {
+	let source = "const A = B + 1\nconst B = 2\n\nproc main\n\tvar a := A\n\tdrop a := 3\nreturn\n";
+	run(source).unwrap();
+	
+	let source = "const A = B + 1\nconst B = A\n\nproc main\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::CyclicConst(_))));
+}
+
+// these use syntax that isn't supported yet. once they parse, they come off
+// this list and get checked like the rest.
+const UNFINISHED_EXAMPLES: &[&str] = &["lisp_calc.rvr", "stdlib.rvr"];
+
+#[test]
+fn call_forms() {
+	run(include_str!("../tests/roundtrip/calls.rvr")).unwrap();
+	
+	let source = "proc main\n\tdo show:\nreturn\n";
+	assert!(Parser::new(source).parse_file_module().is_err());
+}
+
+#[test]
+fn not_and_negate() {
+	run(include_str!("../tests/roundtrip/unary.rvr")).unwrap();
+}
+
+#[test]
+fn sized_ints() {
+	run(include_str!("../tests/roundtrip/ints.rvr")).unwrap();
+	
+	let source = "proc main\n\tvar b: U8 := 256\n\tdrop b := 0\nreturn\n";
+	assert!(matches!(run(source), Err(EvalError::OutOfRange { typ: Type::U8, .. })));
+}
+
+#[test]
+fn chars() {
+	run(include_str!("../tests/roundtrip/chars.rvr")).unwrap();
+}
+
+#[test]
+fn char_out_of_range() {
+	// below zero, then into the surrogates
+	for amount in ["-= 98", "+= 55200"] {
+		let source = format!("proc main\n\tvar c := 'a'\n\tc {amount}\n\tdrop c := 'a'\nreturn\n");
+		assert!(matches!(run(&source), Err(EvalError::OutOfRange { typ: Type::Char, .. })));
+	}
+}
+
+#[test]
+fn bools() {
+	run(include_str!("../tests/roundtrip/bools.rvr")).unwrap();
+}
+
+#[test]
+fn bool_isnt_u32() {
+	let source = "proc inc {x: U32}\n\tx += 1\nreturn\n\nproc main\n\tvar b: Bool := True\n\tdo inc {b}\n\tdrop b := True\nreturn\n";
+	assert!(matches!(
+		check(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Bool })
+	));
+	
+	let source = "proc main\n\tvar n := 1\n\tn := True\n\tdrop n := 0\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Bool })
+	));
+}
+
+#[test]
+fn aliases() {
+	run(include_str!("../tests/roundtrip/aliases.rvr")).unwrap();
+}
+
+#[test]
+fn alias_errors() {
+	// the error names the alias rather than what it stands for
+	let source = "alias Word = U32\n\nproc main\n\tvar w: Word := 'a'\n\tdrop w := 'a'\nreturn\n";
+	match run(source) {
+		Err(EvalError::TypeMismatch { expected: Type::Decl(name, _), got: Type::Char }) =>
+			assert_eq!(name, "Word"),
+		other => panic!("expected a type mismatch, got {:?}", other),
+	}
+	
+	let source = "alias A = 04 -> B\nalias B = A\n\nproc main\n\tskip\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::CyclicAlias(_))));
+}
+
+#[test]
+fn tuples() {
+	run(include_str!("../tests/roundtrip/tuples.rvr")).unwrap();
+}
+
+#[test]
+fn tuple_length_mismatch() {
+	let source = "proc main\n\tvar p := (1, 2)\n\tp += (1, 2, 3)\n\tdrop p := (1, 2)\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::Tuple(a), got: Type::Tuple(b) })
+			if a.len() == 2 && b.len() == 3
+	));
+}
+
+#[test]
+fn proc_values() {
+	run(include_str!("../tests/roundtrip/procs.rvr")).unwrap();
+}
+
+#[test]
+fn proc_value_type() {
+	let source = "\
+proc flip {b: Bool}
+	not b
+return
+
+proc apply {const f: proc(var U32), x: U32}
+	do f {x}
+return
+
+proc main
+	var x := 1
+	do apply {flip, x}
+	drop x := 1
+return
+";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::Proc(_), got: Type::Proc(_) })
+	));
+}
+
+#[test]
+fn let_bindings() {
+	run(include_str!("../tests/roundtrip/lets.rvr")).unwrap();
+}
+
+#[test]
+fn let_errors() {
+	// a binding can't reuse a parameter's name or an earlier binding's
+	for binding in ["let x = 1", "let a = 1, a = 2"] {
+		let source = format!("fn f(x: U32): U32\n\t{binding}\n\tx\n\nproc main\n\tskip\nreturn\n");
+		assert!(matches!(check(&source), Err(EvalError::Rebinding(_))));
+	}
+	
+	let source = "fn f(x: U32): U32\n\tlet y: Bool = x\n\tx\n\nproc main\n\tvar a := f(1)\n\tdrop a := 1\nreturn\n";
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::Bool, got: Type::U32 })
+	));
+}
+
+#[test]
+fn casts() {
+	run(include_str!("../tests/roundtrip/casts.rvr")).unwrap();
+}
+
+#[test]
+fn lossy_casts() {
+	// `01` only holds 0, so `True` doesn't fit
+	for (name, typ, value, cast) in [("b", "Bool", "True", "01"), ("n", "U32", "2", "Bool"), ("m", "I8", "-1", "U32")] {
+		let source = format!(
+			"proc main\n\tvar {name}: {typ} := {value}\n\tvar x := {name} as {cast}\n\tdrop x := {name} as {cast}\n\tdrop {name} := {value}\nreturn\n"
+		);
+		assert!(matches!(run(&source), Err(EvalError::LossyCast { .. })));
+	}
+}
+
+#[test]
+fn examples() {
+	let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+	
+	for entry in std::fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		let name = path.file_name().unwrap().to_str().unwrap().to_string();
+		let source = std::fs::read_to_string(&path).unwrap();
+		let mut parser = Parser::new(&source);
+		let items = parser.parse_file_module();
+		
+		if UNFINISHED_EXAMPLES.contains(&name.as_str()) {
+			assert!(items.is_err(), "{} parses now", name);
+			continue;
+		}
+		let items = match items {
+			Ok(items) => items,
+			Err(e) => panic!("{}: parser error at line {}: {}", name, parser.line(), e),
+		};
+		if let Err(e) = interpret::load_file(items) {
+			panic!("{}: {}", name, e);
+		}
+	}
+}
+
+#[test]
+fn generics() {
+	run(include_str!("../tests/roundtrip/generics.rvr")).unwrap();
+}
+
+#[test]
+fn generic_instantiation() {
+	// `T` can't be both `U32` and `Char`, which the declared types already show
+	let source = "proc same[T] {a: T, b: T}\n\tskip\nreturn\n\nproc main\n\tvar a: U32 := 1\n\tvar c: Char := 'c'\n\tdo same {a, c}\n\tdrop c := 'c'\n\tdrop a := 1\nreturn\n";
+	assert!(matches!(
+		check(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Char })
+	));
+	
+	let source = "struct Pair[T] {left: T, right: T}\n\nproc main\n\tvar p: Pair[U32, U32] := Pair {left: 1, right: 2}\n\tdrop p := Pair {left: 1, right: 2}\nreturn\n";
+	assert!(matches!(check(source), Err(EvalError::TypeArity { expected: 1, got: 2, .. })));
+	
+	let source = "proc main\n\tvar x: U32[Char] := 1\n\tdrop x := 1\nreturn\n";
+	assert!(Parser::new(source).parse_file_module().is_err());
+	
+	// a struct's type arguments come from its fields, so this is only caught
+	// while running
+	let source = "struct Pair[T] {left: T, right: T}\n\nproc main\n\tvar p := Pair {left: 1, right: 'a'}\n\tdrop p := Pair {left: 1, right: 'a'}\nreturn\n";
+	check(source).unwrap();
+	assert!(matches!(
+		run(source),
+		Err(EvalError::TypeMismatch { expected: Type::U32, got: Type::Char })
+	));
+}
@@ -10,6 +10,7 @@ pub enum Token {
 	// keywords
 	#[token("and")]    And,
 	#[token("const")]  Const,
+	#[token("cswap")]  CSwap,
 	#[token("do")]     Do,
 	#[token("drop")]   Drop,
 	#[token("else")]   Else,
@@ -21,14 +22,21 @@ pub enum Token {
 	#[token("let")]    Let,
 	#[token("loop")]   Loop,
 	#[token("module")] Mod,
+	#[token("nil")]    Nil,
 	#[token("not")]    Not,
 	#[token("or")]     Or,
 	#[token("proc")]   Proc,
 	#[token("return")] Return,
+	#[token("rol")]    Rol,
+	#[token("ror")]    Ror,
+	#[token("shl")]    Shl,
+	#[token("shr")]    Shr,
 	#[token("skip")]   Skip,
 	#[token("undo")]   Undo,
 	#[token("until")]  Until,
+	#[token("use")]    Use,
 	#[token("var")]    Var,
+	#[token("xor")]    Xor,
 	
 	// reserved keywords
 	#[token("alias")]  Alias,
@@ -59,13 +67,17 @@ pub enum Token {
 	#[token("<=")] Lte,
 	#[token(">=")] Gte,
 	
+	// shifts
+	#[token("<<")] LShift,
+	#[token(">>")] RShift,
+	
 	// assignments
 	#[token("<>")] Swap,
 	#[token(":=")] Assign,
 	#[token("+=")] AddAssign,
 	#[token("-=")] SubAssign,
-	//#[token("*=")] MulAssign,
-	//#[token("/=")] DivAssign,
+	#[token("*=")] MulAssign,
+	#[token("/=")] DivAssign,
 	#[token(":<")] RolAssign,
 	#[token(":>")] RorAssign,
 	#[token("^=")] XorAssign,
@@ -73,8 +85,10 @@ pub enum Token {
 	// multi-purpose
 	#[token(":")] Colon,
 	#[token(".")] Period,
+	#[token("..")] Range,
 	#[token(",")] Comma,
 	#[token(";")] Semicolon,
+	#[token("::")] Scope,
 	
 	#[token("+")] Plus,
 	#[token("-")] Minus,
@@ -83,11 +97,9 @@ pub enum Token {
 	#[token("%")] Percent,
 	#[token("!")] Bang,
 	#[token("^")] Caret,
-	#[token("~")] Tilde,
 	#[token("_")] Underscore,
 	
 	// unused
-	#[token("::")] Scope,
 	#[token("->")] RightArrow,
 	#[token("?")]  QMark,
 	#[token(":-")] Impls,
@@ -116,6 +128,7 @@ pub enum Token {
 	Char,
 	
 	#[regex("#.*", logos::skip)]
+	#[regex("~.*", logos::skip)]
 	Comment,
 	
 	#[error]